@@ -0,0 +1,138 @@
+// times reading every save in tests/fixtures and a large synthetic one, run it once per mode
+// to compare sequential and parallel object parsing:
+//   cargo bench --bench parse
+//   cargo bench --bench parse --features parallel
+
+use std::fs;
+use std::path::Path;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use remnant_save_parser::{Compressor, ObjectPadding, Reader, SavFile, SaveGameArchive, Writer};
+use serde_json::{json, Value};
+
+const OBJECT_COUNT: usize = 20_000;
+
+fn property(name: &str, type_name: &str, data: Value) -> Value {
+    json!({
+        "name": { "value": name },
+        "index": 0,
+        "type_name": { "value": type_name },
+        "size": 0,
+        "data": data
+    })
+}
+
+fn object(object_id: usize) -> Value {
+    let elements = (0..16).map(|i| json!({ "Int32": i })).collect::<Vec<_>>();
+
+    json!({
+        "object_id": object_id,
+        "was_loaded": false,
+        "object_path": format!("/Game/World_Base/Actors/Actor_{}", object_id),
+        "loaded_data": { "name": { "value": "Actor_C", "number": object_id }, "outer_id": 0 },
+        "properties": [
+            property("Level", "IntProperty", json!({ "Int32": object_id })),
+            property("Label", "StrProperty", json!({ "Str": format!("Actor number {}", object_id) })),
+            property("Values", "ArrayProperty", json!({ "Array": {
+                "inner_type": { "value": "IntProperty" },
+                "head_data": "None",
+                "elements": elements
+            } })),
+        ],
+        "components": null
+    })
+}
+
+fn synthetic_save() -> Vec<u8> {
+    let class_path = "/Game/_Core/Blueprints/Base/BP_RemnantSaveGame";
+
+    let mut objects = vec![json!({
+        "object_id": 0,
+        "was_loaded": true,
+        "object_path": class_path,
+        "loaded_data": null,
+        "properties": [property("Level", "IntProperty", json!({ "Int32": 1 }))],
+        "components": null
+    })];
+
+    objects.extend((1..OBJECT_COUNT).map(object));
+
+    let archive: SaveGameArchive = serde_json::from_value(json!({
+        "header": { "save_game_file_version": 9, "build_number": 400000 },
+        "content": {
+            "package_version": { "ue4_version": 522, "ue5_version": 1008 },
+            "save_game_class_path": { "path": class_path, "name": "BP_RemnantSaveGame_C" },
+            "name_table": { "list": [] },
+            "object_index": objects,
+            "version": 1
+        }
+    })).expect("synthetic archive");
+
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+    SavFile::write(&mut writer, &archive, &Compressor::None).expect("write synthetic archive");
+
+    writer.into_inner()
+}
+
+// `(name, bytes)` of the synthetic save and every save in tests/fixtures
+fn saves() -> Vec<(String, Vec<u8>)> {
+    let mut saves = vec![(format!("synthetic_{}_objects", OBJECT_COUNT), synthetic_save())];
+
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries {
+            let path = entry.expect("fixture entry").path();
+
+            if path.extension().is_some_and(|extension| extension == "sav") {
+                let name = path.file_stem().expect("fixture name").to_string_lossy().into_owned();
+
+                saves.push((name, fs::read(&path).expect("read fixture")));
+            }
+        }
+    }
+
+    saves
+}
+
+// decompression and archive parsing, i.e. what loading a save costs
+fn read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read");
+
+    for (name, bytes) in saves() {
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &bytes, |b, bytes| {
+            b.iter(|| {
+                SavFile::read(&mut Reader::new(bytes.clone(), ObjectPadding::Four))
+                    .and_then(|sav_file| sav_file.get_archive())
+                    .expect("read save")
+            });
+        });
+    }
+
+    group.finish();
+}
+
+// only the archive parsing, decompression is done once up front
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group(if cfg!(feature = "parallel") { "parse_parallel" } else { "parse" });
+
+    for (name, bytes) in saves() {
+        let sav_file = SavFile::read(&mut Reader::new(bytes, ObjectPadding::Four)).expect("read sav file");
+        let content = sav_file.get_content().expect("decompress");
+
+        group.throughput(Throughput::Bytes(content.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &content, |b, content| {
+            b.iter(|| SaveGameArchive::read(&mut Reader::new(content.clone(), ObjectPadding::Four)).expect("read archive"));
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = read, parse
+}
+criterion_main!(benches);
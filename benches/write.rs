@@ -0,0 +1,167 @@
+// times writing every save in tests/fixtures and a synthetic one with many distinct names,
+// which stresses the name table, with and without preallocating the output, as well as the
+// name lookup on its own:
+//   cargo bench --bench write
+
+use std::fs;
+use std::path::Path;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use remnant_save_parser::{Compressor, FName, ObjectPadding, Reader, SavFile, SaveGameArchive, Writer};
+use remnant_save_parser::sav::NameTable;
+use serde_json::{json, Value};
+
+const OBJECT_COUNT: usize = 2_000;
+const NAMES_PER_OBJECT: usize = 10;
+
+fn property(name: &str, type_name: &str, data: Value) -> Value {
+    json!({
+        "name": { "value": name },
+        "index": 0,
+        "type_name": { "value": type_name },
+        "size": 0,
+        "data": data
+    })
+}
+
+fn object(object_id: usize, class_path: &str) -> Value {
+    let properties = (0..NAMES_PER_OBJECT)
+        .map(|i| property(
+            &format!("Field_{}_{}", object_id, i),
+            "IntProperty",
+            json!({ "Int32": i }),
+        ))
+        .collect::<Vec<_>>();
+
+    json!({
+        "object_id": object_id,
+        "was_loaded": true,
+        "object_path": match object_id {
+            0 => class_path.to_owned(),
+            _ => format!("/Game/World_Base/Actors/Actor_{}", object_id),
+        },
+        "loaded_data": null,
+        "properties": properties,
+        "components": null
+    })
+}
+
+fn synthetic_archive() -> SaveGameArchive {
+    let class_path = "/Game/_Core/Blueprints/Base/BP_RemnantSaveGame";
+
+    serde_json::from_value(json!({
+        "header": { "save_game_file_version": 9, "build_number": 400000 },
+        "content": {
+            "package_version": { "ue4_version": 522, "ue5_version": 1008 },
+            "save_game_class_path": { "path": class_path, "name": "BP_RemnantSaveGame_C" },
+            "name_table": { "list": [] },
+            "object_index": (0..OBJECT_COUNT).map(|object_id| object(object_id, class_path)).collect::<Vec<_>>(),
+            "version": 1
+        }
+    })).expect("synthetic archive")
+}
+
+// `(name, archive, source file)` of the synthetic archive and every save in tests/fixtures,
+// fixtures are written with the compressor they were read with
+fn archives() -> Vec<(String, SaveGameArchive, Option<SavFile>)> {
+    let mut archives = vec![(
+        format!("synthetic_{}_names", OBJECT_COUNT * NAMES_PER_OBJECT),
+        synthetic_archive(),
+        None,
+    )];
+
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries {
+            let path = entry.expect("fixture entry").path();
+
+            if path.extension().is_some_and(|extension| extension == "sav") {
+                let name = path.file_stem().expect("fixture name").to_string_lossy().into_owned();
+                let sav_file = SavFile::read(&mut Reader::new(fs::read(&path).expect("read fixture"), ObjectPadding::Four))
+                    .expect("read sav file");
+                let archive = sav_file.get_archive().expect("read archive");
+
+                archives.push((name, archive, Some(sav_file)));
+            }
+        }
+    }
+
+    archives
+}
+
+fn write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write");
+
+    for (name, archive, sav_file) in archives() {
+        let compressor = sav_file.as_ref().map_or(&Compressor::None, SavFile::compressor);
+
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+                SavFile::write(&mut writer, &archive, compressor).expect("write archive");
+
+                writer.into_inner()
+            });
+        });
+    }
+
+    group.finish();
+}
+
+// the same with the output preallocated to the content size, like when rewriting a save that was read
+fn write_with_capacity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write_with_capacity");
+
+    for (name, archive, sav_file) in archives() {
+        let compressor = sav_file.as_ref().map_or(&Compressor::None, SavFile::compressor);
+        let capacity = match &sav_file {
+            Some(sav_file) => sav_file.content_size as usize,
+            None => {
+                let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+                SavFile::write(&mut writer, &archive, compressor).expect("write archive");
+
+                writer.into_inner().len()
+            }
+        };
+
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                let mut writer = Writer::with_capacity(capacity, ObjectPadding::Four);
+
+                SavFile::write(&mut writer, &archive, compressor).expect("write archive");
+
+                writer.into_inner()
+            });
+        });
+    }
+
+    group.finish();
+}
+
+// every name is added once and then looked up again, like repeated property names in a save
+fn write_name(c: &mut Criterion) {
+    let names = (0..OBJECT_COUNT).map(|i| FName::from(&format!("Name_{}", i))).collect::<Vec<_>>();
+
+    c.bench_function("write_name", |b| {
+        b.iter_batched(
+            || (NameTable::default(), Writer::new(Vec::new(), ObjectPadding::Four)),
+            |(mut name_table, mut writer)| {
+                for name in names.iter().chain(&names) {
+                    name_table.write_name(&mut writer, name).expect("write name");
+                }
+
+                name_table
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = write, write_with_capacity, write_name
+}
+criterion_main!(benches);
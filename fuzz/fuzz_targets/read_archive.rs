@@ -0,0 +1,10 @@
+// decompressed archive content, i.e. the name table, object index and properties
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use remnant_save_parser::{ObjectPadding, Reader, SaveGameArchive};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = SaveGameArchive::read(&mut Reader::new(data.to_vec(), ObjectPadding::Four));
+});
@@ -0,0 +1,13 @@
+// a whole save file, mostly exercises the chunk headers and decompression
+// since random input rarely gets past the checksum
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use remnant_save_parser::{ObjectPadding, Reader, SavFile};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(sav_file) = SavFile::read(&mut Reader::new(data.to_vec(), ObjectPadding::Four)) {
+        let _ = sav_file.get_archive();
+    }
+});
@@ -1,6 +1,7 @@
 mod reader;
 mod writer;
 
+use anyhow::bail;
 use serde::{Deserialize, Serialize};
 use crate::properties::Property;
 use crate::structs::FName;
@@ -40,10 +41,144 @@ pub enum VariableValue {
     Bool(bool),
     Int(i32),
     Float(f32),
-    Name(FName), // TODO: is this correct?
+    Name(FName), // var_type 4, unconfirmed
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DynamicStructComponent {
     pub properties: Vec<Property>,
-}
\ No newline at end of file
+}
+
+// utility functions
+
+impl Variables {
+    pub fn get(&self, name: &str) -> Option<&VariableValue> {
+        self.variables.iter().find(|variable| variable.name.matches(name)).map(|variable| &variable.value)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut VariableValue> {
+        self.variable_mut(name).map(|variable| &mut variable.value)
+    }
+
+    pub fn variable_mut(&mut self, name: &str) -> Option<&mut Variable> {
+        self.variables.iter_mut().find(|variable| variable.name.matches(name))
+    }
+
+    // updates the variable in place or appends it, so existing ones keep their position
+    pub fn set(&mut self, name: &str, value: VariableValue) {
+        match self.get_mut(name) {
+            Some(existing) => *existing = value,
+            None => self.variables.push(Variable::new(name, value)),
+        }
+    }
+
+    // existing variables keep their type, missing ones are appended with the given one
+    pub fn set_bool(&mut self, name: &str, value: bool) -> anyhow::Result<()> {
+        if let Some(variable) = self.variable_mut(name) {
+            return variable.set_bool(value);
+        }
+
+        self.variables.push(Variable::new(name, VariableValue::Bool(value)));
+
+        Ok(())
+    }
+
+    pub fn set_int(&mut self, name: &str, value: i32) -> anyhow::Result<()> {
+        if let Some(variable) = self.variable_mut(name) {
+            return variable.set_int(value);
+        }
+
+        self.variables.push(Variable::new(name, VariableValue::Int(value)));
+
+        Ok(())
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) -> anyhow::Result<()> {
+        if let Some(variable) = self.variable_mut(name) {
+            return variable.set_float(value);
+        }
+
+        self.variables.push(Variable::new(name, VariableValue::Float(value)));
+
+        Ok(())
+    }
+
+    pub fn set_name(&mut self, name: &str, value: &str) -> anyhow::Result<()> {
+        if let Some(variable) = self.variable_mut(name) {
+            return variable.set_name(value);
+        }
+
+        self.variables.push(Variable::new(name, VariableValue::Name(FName::from(value))));
+
+        Ok(())
+    }
+}
+
+impl Variable {
+    pub fn new(name: &str, value: VariableValue) -> Variable {
+        Variable {
+            name: FName::from(name),
+            value,
+        }
+    }
+
+    // the typed setters keep the variable's type, since the game reads it from the var_type byte
+
+    pub fn set_bool(&mut self, value: bool) -> anyhow::Result<()> {
+        match &mut self.value {
+            VariableValue::Bool(data) => *data = value,
+            data => bail!("{} is not a bool variable: {:?}", self.name, data),
+        }
+
+        Ok(())
+    }
+
+    pub fn set_int(&mut self, value: i32) -> anyhow::Result<()> {
+        match &mut self.value {
+            VariableValue::Int(data) => *data = value,
+            data => bail!("{} is not an int variable: {:?}", self.name, data),
+        }
+
+        Ok(())
+    }
+
+    pub fn set_float(&mut self, value: f32) -> anyhow::Result<()> {
+        match &mut self.value {
+            VariableValue::Float(data) => *data = value,
+            data => bail!("{} is not a float variable: {:?}", self.name, data),
+        }
+
+        Ok(())
+    }
+
+    pub fn set_name(&mut self, value: &str) -> anyhow::Result<()> {
+        match &mut self.value {
+            VariableValue::Name(data) => *data = FName::from(value),
+            data => bail!("{} is not a name variable: {:?}", self.name, data),
+        }
+
+        Ok(())
+    }
+
+    // converts `value` to the variable's current type, numbers only convert losslessly
+    pub fn set_coerced(&mut self, value: VariableValue) -> anyhow::Result<()> {
+        let coerced = match (&self.value, value) {
+            (VariableValue::Bool(_), VariableValue::Bool(value)) => VariableValue::Bool(value),
+            (VariableValue::Bool(_), VariableValue::Int(value @ (0 | 1))) => VariableValue::Bool(value == 1),
+            (VariableValue::Bool(_), VariableValue::Float(value)) if value == 0.0 || value == 1.0 => VariableValue::Bool(value == 1.0),
+            (VariableValue::Int(_), VariableValue::Bool(value)) => VariableValue::Int(value as i32),
+            (VariableValue::Int(_), VariableValue::Int(value)) => VariableValue::Int(value),
+            (VariableValue::Int(_), VariableValue::Float(value)) if value.fract() == 0.0
+                && value >= i32::MIN as f32 && value < i32::MAX as f32 => VariableValue::Int(value as i32),
+            (VariableValue::Float(_), VariableValue::Bool(value)) => VariableValue::Float(value as i32 as f32),
+            (VariableValue::Float(_), VariableValue::Int(value)) if value as f32 as i32 == value => VariableValue::Float(value as f32),
+            (VariableValue::Float(_), VariableValue::Float(value)) => VariableValue::Float(value),
+            (VariableValue::Name(_), VariableValue::Name(value)) => VariableValue::Name(value),
+            (data, value) => bail!("{:?} can't be converted to the type of {} ({:?})", value, self.name, data),
+        };
+
+        self.value = coerced;
+
+        Ok(())
+    }
+}
@@ -1,13 +1,13 @@
 use anyhow::bail;
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::ReadBytesExt;
 use crate::components::{ComponentType, DynamicStructComponent, Variable, Variables, VariableValue};
-use crate::io::Reader;
+use crate::io::{ArchiveReader, Endian};
 use crate::properties::Property;
 use crate::sav::SaveGameArchiveContent;
 
 impl ComponentType {
-    pub fn read(
-        reader: &mut Reader,
+    pub fn read<R: ArchiveReader>(
+        reader: &mut R,
         sav_data: &SaveGameArchiveContent,
         name: &str,
     ) -> anyhow::Result<Self> {
@@ -52,16 +52,16 @@ impl ComponentType {
 }
 
 impl Variables {
-    pub fn read(reader: &mut Reader, sav_data: &SaveGameArchiveContent) -> anyhow::Result<Self> {
+    pub fn read<R: ArchiveReader>(reader: &mut R, sav_data: &SaveGameArchiveContent) -> anyhow::Result<Self> {
         let name = sav_data.read_name(reader)?;
-        let empty = reader.read_u64::<LittleEndian>()?;
+        let empty = reader.read_u64::<Endian>()?;
 
         if empty != 0 {
-            bail!("Variables::read: {:X}", empty);
+            bail!("Variables {} have non-zero padding {:#X} before their count", name.value, empty);
         }
 
-        let count = reader.read_u32::<LittleEndian>()?;
-        let mut variables = Vec::with_capacity(count as usize);
+        let count = reader.read_u32::<Endian>()?;
+        let mut variables = Vec::with_capacity(reader.capacity_for(count));
 
         for _ in 0..count {
             let variable = Variable::read(reader, sav_data)?;
@@ -74,17 +74,19 @@ impl Variables {
 }
 
 impl Variable {
-    pub fn read(reader: &mut Reader, sav_data: &SaveGameArchiveContent) -> anyhow::Result<Self> {
+    pub fn read<R: ArchiveReader>(reader: &mut R, sav_data: &SaveGameArchiveContent) -> anyhow::Result<Self> {
         let name = sav_data.read_name(reader)?;
         let var_type = reader.read_u8()?;
 
         let value = match var_type {
             0 => VariableValue::None,
-            1 => VariableValue::Bool(reader.read_u32::<LittleEndian>()? != 0),
-            2 => VariableValue::Int(reader.read_i32::<LittleEndian>()?),
-            3 => VariableValue::Float(reader.read_f32::<LittleEndian>()?),
-            4 => VariableValue::Name(sav_data.read_name(reader)?), // TODO: check if this is correct
-            _ => bail!(anyhow::anyhow!("Variable::read: unknown var_type")),
+            1 => VariableValue::Bool(reader.read_u32::<Endian>()? != 0),
+            2 => VariableValue::Int(reader.read_i32::<Endian>()?),
+            3 => VariableValue::Float(reader.read_f32::<Endian>()?),
+            // TODO: unconfirmed against a real save, assumed to be a name table reference like
+            // every other FName in the archive (the writer mirrors this)
+            4 => VariableValue::Name(sav_data.read_name(reader)?),
+            _ => bail!("Variable {} has an unknown type {}", name.value, var_type),
         };
 
         Ok(Variable { name, value })
@@ -92,12 +94,13 @@ impl Variable {
 }
 
 impl DynamicStructComponent {
-    pub fn read(reader: &mut Reader, sav_data: &SaveGameArchiveContent) -> anyhow::Result<Self> {
+    pub fn read<R: ArchiveReader>(reader: &mut R, sav_data: &SaveGameArchiveContent) -> anyhow::Result<Self> {
         let properties = Property::read_multiple(reader, sav_data)?;
-        let empty = reader.read_u64::<LittleEndian>()?;
+        // see DynamicStructComponent::write
+        let empty = reader.read_u64::<Endian>()?;
 
         if empty != 0 {
-            bail!("DynamicStruct::read: {:X?}", empty);
+            bail!("Dynamic struct component has non-zero padding {:#X} after its properties", empty);
         }
 
         Ok(DynamicStructComponent { properties })
@@ -1,13 +1,13 @@
-use byteorder::{LittleEndian, WriteBytesExt};
+use byteorder::WriteBytesExt;
 use crate::components::{ComponentType, DynamicStructComponent, Variable, Variables, VariableValue};
-use crate::io::Writer;
+use crate::io::{ArchiveWriter, Endian};
 use crate::properties::Property;
 use crate::sav::NameTable;
 
 impl ComponentType {
-    pub fn write(
+    pub fn write<W: ArchiveWriter>(
         &self,
-        writer: &mut Writer,
+        writer: &mut W,
         name_table: &mut NameTable,
     ) -> anyhow::Result<()> {
         match self {
@@ -39,14 +39,14 @@ impl ComponentType {
 }
 
 impl Variables {
-    pub fn write(
+    pub fn write<W: ArchiveWriter>(
         &self,
-        writer: &mut Writer,
+        writer: &mut W,
         name_table: &mut NameTable,
     ) -> anyhow::Result<()> {
         name_table.write_name(writer, &self.name)?;
-        writer.write_u64::<LittleEndian>(0)?;
-        writer.write_u32::<LittleEndian>(self.variables.len() as u32)?;
+        writer.write_u64::<Endian>(0)?;
+        writer.write_u32::<Endian>(self.variables.len() as u32)?;
 
         for variable in &self.variables {
             variable.write(writer, name_table)?;
@@ -57,9 +57,9 @@ impl Variables {
 }
 
 impl Variable {
-    pub fn write(
+    pub fn write<W: ArchiveWriter>(
         &self,
-        writer: &mut Writer,
+        writer: &mut W,
         name_table: &mut NameTable,
     ) -> anyhow::Result<()> {
         name_table.write_name(writer, &self.name)?;
@@ -67,21 +67,21 @@ impl Variable {
         match &self.value {
             VariableValue::None => {
                 writer.write_u8(0)?;
-                writer.write_u32::<LittleEndian>(0)?;
+                writer.write_u32::<Endian>(0)?;
             }
             VariableValue::Bool(value) => {
                 writer.write_u8(1)?;
-                writer.write_u32::<LittleEndian>(*value as u32)?;
+                writer.write_u32::<Endian>(*value as u32)?;
             }
             VariableValue::Int(value) => {
                 writer.write_u8(2)?;
-                writer.write_u32::<LittleEndian>(*value as u32)?;
+                writer.write_u32::<Endian>(*value as u32)?;
             }
             VariableValue::Float(value) => {
                 writer.write_u8(3)?;
-                writer.write_f32::<LittleEndian>(*value)?;
+                writer.write_f32::<Endian>(*value)?;
             }
-            VariableValue::Name(value) => { // TODO: Check if this is correct
+            VariableValue::Name(value) => { // see Variable::read
                 writer.write_u8(4)?;
                 name_table.write_name(writer, value)?;
             }
@@ -92,9 +92,9 @@ impl Variable {
 }
 
 impl DynamicStructComponent {
-    pub fn write(
+    pub fn write<W: ArchiveWriter>(
         &self,
-        writer: &mut Writer,
+        writer: &mut W,
         name_table: &mut NameTable,
     ) -> anyhow::Result<()> {
         for field in &self.properties {
@@ -103,7 +103,8 @@ impl DynamicStructComponent {
 
         Property::write_none(writer, name_table)?;
 
-        writer.write_u64::<LittleEndian>(0)?;
+        // components carry an extra u64 after the terminator, struct values do not
+        writer.write_u64::<Endian>(0)?;
 
         Ok(())
     }
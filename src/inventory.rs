@@ -0,0 +1,223 @@
+use anyhow::bail;
+use crate::properties::{Property, PropertyData};
+use crate::sav::{SaveGameArchive, SaveGameArchiveContent, UObject};
+use crate::structs::{DynamicStruct, FName, StructData};
+
+// inventory components keep their items in an array of structs, where equipped
+// items carry the slot they are equipped in and a reference to their blueprint
+pub const INVENTORY_ITEMS_PROPERTY: &str = "Items";
+pub const ITEM_BLUEPRINT_PROPERTY: &str = "ItemBP";
+pub const EQUIPMENT_SLOT_PROPERTY: &str = "EquipmentSlotIndex";
+const QUANTITY_PROPERTY: &str = "Quantity";
+const LEVEL_PROPERTY: &str = "Level";
+
+// the items of all inventories in a save, edits are written back with `apply`
+#[derive(Debug, Default)]
+pub struct Inventory {
+    pub items: Vec<InventoryEntry>,
+}
+
+// an item and where it is stored, by its archive in SaveGameArchiveContent::archives order,
+// the inventory object and its index in the inventory's items
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventoryEntry {
+    pub id: FName, // name of the item blueprint, e.g. `Relic_Consumable_Dragonheart_C`
+    pub item: InventoryItem,
+    archive: usize,
+    object: usize,
+    index: usize,
+}
+
+// typed copy of an inventory item struct, edits are written back with `apply`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventoryItem {
+    pub item_bp: i32, // object index of the item blueprint
+    pub quantity: Option<i32>,
+    pub level: Option<i32>,
+    pub equipment_slot: Option<i32>,
+}
+
+impl InventoryItem {
+    // None if the struct doesn't look like an inventory item,
+    // callers should fall back to the generic DynamicStruct then
+    pub fn from_struct(item: &DynamicStruct) -> Option<InventoryItem> {
+        let item_bp = match Self::field(&item.properties, ITEM_BLUEPRINT_PROPERTY)? {
+            PropertyData::Object(index) => *index,
+            _ => return None,
+        };
+
+        Some(InventoryItem {
+            item_bp,
+            quantity: Self::int_field(&item.properties, QUANTITY_PROPERTY),
+            level: Self::int_field(&item.properties, LEVEL_PROPERTY),
+            equipment_slot: Self::int_field(&item.properties, EQUIPMENT_SLOT_PROPERTY),
+        })
+    }
+
+    pub fn apply(&self, item: &mut DynamicStruct) -> anyhow::Result<()> {
+        Self::set_field(&mut item.properties, ITEM_BLUEPRINT_PROPERTY, PropertyData::Object(self.item_bp))?;
+
+        let fields = [
+            (QUANTITY_PROPERTY, self.quantity),
+            (LEVEL_PROPERTY, self.level),
+            (EQUIPMENT_SLOT_PROPERTY, self.equipment_slot),
+        ];
+
+        for (name, value) in fields {
+            if let Some(value) = value {
+                Self::set_field(&mut item.properties, name, PropertyData::Int32(value))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // items of an inventory object, leaving out elements that aren't inventory items
+    pub fn read_all(object: &UObject) -> Vec<InventoryItem> {
+        match object.get(INVENTORY_ITEMS_PROPERTY).map(|property| &property.data) {
+            Some(PropertyData::Array(items)) => items.elements
+                .iter()
+                .filter_map(|element| match element {
+                    PropertyData::Struct(property) => match &property.data {
+                        StructData::Dynamic(item) => InventoryItem::from_struct(item),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn field<'a>(properties: &'a [Property], name: &str) -> Option<&'a PropertyData> {
+        properties.iter()
+            .find(|property| property.name.matches(name))
+            .map(|property| &property.data)
+    }
+
+    fn int_field(properties: &[Property], name: &str) -> Option<i32> {
+        match Self::field(properties, name)? {
+            PropertyData::Int32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn set_field(properties: &mut [Property], name: &str, value: PropertyData) -> anyhow::Result<()> {
+        let property = match properties.iter_mut().find(|property| property.name.matches(name)) {
+            Some(property) => property,
+            None => bail!("Inventory item has no {} field", name),
+        };
+
+        if std::mem::discriminant(&property.data) != std::mem::discriminant(&value) {
+            bail!("Inventory item field {} is {:?}, not {:?}", name, property.data, value);
+        }
+
+        property.data = value;
+
+        Ok(())
+    }
+}
+
+impl Inventory {
+    // items whose blueprint can't be resolved are left out
+    pub fn read(archive: &SaveGameArchive) -> Inventory {
+        let mut items = Vec::new();
+
+        for (archive, content) in archive.content.archives().into_iter().enumerate() {
+            for (object, inventory) in content.object_index.iter().enumerate() {
+                let elements = match inventory.get(INVENTORY_ITEMS_PROPERTY).map(|property| &property.data) {
+                    Some(PropertyData::Array(items)) => &items.elements,
+                    _ => continue,
+                };
+
+                for (index, element) in elements.iter().enumerate() {
+                    let item = match element {
+                        PropertyData::Struct(property) => match &property.data {
+                            StructData::Dynamic(item) => InventoryItem::from_struct(item),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+
+                    let blueprint = item.as_ref().and_then(|item| content.resolve_object(item.item_bp));
+
+                    if let (Some(item), Some(blueprint)) = (item, blueprint) {
+                        items.push(InventoryEntry {
+                            id: item_name(&blueprint.object_path),
+                            item,
+                            archive,
+                            object,
+                            index,
+                        });
+                    }
+                }
+            }
+        }
+
+        Inventory { items }
+    }
+
+    pub fn items(&self) -> &[InventoryEntry] {
+        &self.items
+    }
+
+    // `id` matches like FName::matches
+    pub fn find<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a InventoryEntry> {
+        self.items.iter().filter(move |entry| entry.id.matches(id))
+    }
+
+    // only items that store a quantity are changed, returns how many were
+    pub fn set_quantity(&mut self, id: &str, quantity: i32) -> usize {
+        let mut changed = 0;
+
+        for entry in self.items.iter_mut().filter(|entry| entry.id.matches(id)) {
+            if entry.item.quantity.is_some() {
+                entry.item.quantity = Some(quantity);
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+
+    // the archive has to be the one the inventory was read from
+    pub fn apply(&self, archive: &mut SaveGameArchive) -> anyhow::Result<()> {
+        let mut index = 0;
+
+        archive.content.for_each_archive_mut(&mut |content| {
+            for entry in self.items.iter().filter(|entry| entry.archive == index) {
+                entry.apply(content)?;
+            }
+
+            index += 1;
+
+            Ok(())
+        })
+    }
+}
+
+impl InventoryEntry {
+    fn apply(&self, content: &mut SaveGameArchiveContent) -> anyhow::Result<()> {
+        let element = content.object_index.get_mut(self.object)
+            .and_then(|object| object.get_mut(INVENTORY_ITEMS_PROPERTY))
+            .and_then(|property| match &mut property.data {
+                PropertyData::Array(items) => items.elements.get_mut(self.index),
+                _ => None,
+            });
+
+        match element {
+            Some(PropertyData::Struct(property)) => match &mut property.data {
+                StructData::Dynamic(item) => self.item.apply(item),
+                _ => bail!("Inventory item {} is not a dynamic struct", self.id),
+            },
+            _ => bail!("Inventory item {} not found at {}[{}]", self.id, INVENTORY_ITEMS_PROPERTY, self.index),
+        }
+    }
+}
+
+// `/Game/Path/To/Item.Item_C` -> `Item_C`
+pub(crate) fn item_name(object_path: &str) -> FName {
+    let name = object_path.rsplit(['.', '/']).next().unwrap_or(object_path);
+
+    FName::from(name)
+}
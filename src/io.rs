@@ -1,54 +1,329 @@
+use crate::properties::PersistenceBlobKind;
+use crate::sav::SaveVersion;
+use anyhow::bail;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::ffi::CString;
 use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
-pub struct Reader {
-    cursor: Cursor<Vec<u8>>,
-    pub object_padding: u32,
+// PC saves are little-endian, a big-endian platform only needs this changed
+pub type Endian = LittleEndian;
+
+// far deeper than any save nests values, but shallow enough that malformed input can't overflow the stack
+pub const MAX_NESTING_DEPTH: usize = 64;
+
+// the zero padding after the property list of object 0: 4 bytes in the top-level archive and 8
+// in every nested one, i.e. persistence blobs and container actors at any depth (each nesting
+// site sets it explicitly rather than inheriting it), other objects always use 4
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectPadding {
+    Four,  // top-level archives
+    Eight, // nested archives
+}
+
+// reads from any seekable stream, by default an in-memory buffer
+pub struct Reader<S = Cursor<Vec<u8>>> {
+    stream: S,
+    position: u64, // tracked here so it can be queried without &mut
+    len: u64,
+    base: u64, // where a sub reader's bytes start in the outermost archive
+    depth: usize, // nesting level of arrays, maps, structs and texts, see nested
+    pub object_padding: ObjectPadding,
+    pub raw_fallback: bool,
+    pub partial_recovery: bool,
+    pub raw_blobs: bool, // keeps persistence blobs and containers as StructData::RawBlob
+    pub record_offsets: bool, // fills in the `offset` of objects and properties
+    pub blob_kinds: Vec<(String, PersistenceBlobKind)>, // save game classes on top of PERSISTENCE_BLOB_KINDS
+    pub save_version: SaveVersion, // set when the header is read, nested archives use the one of their save
+}
+
+impl ObjectPadding {
+    pub fn terminator_size(self) -> usize {
+        match self {
+            ObjectPadding::Four => 4,
+            ObjectPadding::Eight => 8,
+        }
+    }
 }
 
 impl Reader {
-    pub fn new(data: Vec<u8>, object_padding: u32) -> Self {
+    pub fn new(data: Vec<u8>, object_padding: ObjectPadding) -> Self {
         Self {
-            cursor: Cursor::new(data),
+            len: data.len() as u64,
+            stream: Cursor::new(data),
+            position: 0,
+            base: 0,
+            depth: 0,
             object_padding,
+            raw_fallback: false,
+            partial_recovery: false,
+            raw_blobs: false,
+            record_offsets: false,
+            blob_kinds: Vec::new(),
+            save_version: SaveVersion::default(),
         }
     }
 
     pub fn get_ref(&self) -> &Vec<u8> {
-        self.cursor.get_ref()
+        self.stream.get_ref()
+    }
+}
+
+impl<S: Read + Seek> Reader<S> {
+    // reading starts at the current position of the stream
+    pub fn from_stream(mut stream: S, object_padding: ObjectPadding) -> std::io::Result<Self> {
+        let position = stream.stream_position()?;
+        let len = stream.seek(SeekFrom::End(0))?;
+
+        stream.seek(SeekFrom::Start(position))?;
+
+        Ok(Self {
+            stream,
+            position,
+            len,
+            base: 0,
+            depth: 0,
+            object_padding,
+            raw_fallback: false,
+            partial_recovery: false,
+            raw_blobs: false,
+            record_offsets: false,
+            blob_kinds: Vec::new(),
+            save_version: SaveVersion::default(),
+        })
+    }
+
+    // `data` has to be the bytes that were just read, that's where the sub reader's base comes from
+    pub fn sub_reader(&self, data: Vec<u8>, object_padding: ObjectPadding) -> Reader {
+        Reader {
+            len: data.len() as u64,
+            base: self.absolute_position() - data.len() as u64,
+            depth: self.depth,
+            stream: Cursor::new(data),
+            position: 0,
+            object_padding,
+            raw_fallback: self.raw_fallback,
+            partial_recovery: false, // a broken nested archive truncates its outer archive instead
+            raw_blobs: self.raw_blobs,
+            record_offsets: self.record_offsets,
+            blob_kinds: self.blob_kinds.clone(),
+            save_version: self.save_version,
+        }
     }
 
     pub fn position(&self) -> u64 {
-        self.cursor.position()
+        self.position
+    }
+
+    // the position in the outermost archive, for sub readers of nested archives
+    pub fn absolute_position(&self) -> u64 {
+        self.base + self.position
+    }
+
+    // the length of the stream when the reader was created
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.len.saturating_sub(self.position)
+    }
+
+    // for offsets read from the file, which would otherwise seek past the end and
+    // only fail on the next read
+    pub fn seek_checked(&mut self, offset: u64) -> anyhow::Result<()> {
+        if offset > self.len {
+            bail!("Offset {:#X} is past the end of the data ({:#X} bytes)", offset, self.len);
+        }
+
+        self.seek(SeekFrom::Start(offset))?;
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
     }
 }
 
-impl Read for Reader {
+impl<S: Read> Read for Reader<S> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.cursor.read(buf)
+        let read = self.stream.read(buf)?;
+
+        self.position += read as u64;
+
+        Ok(read)
     }
 }
 
-impl Seek for Reader {
+impl<S: Seek> Seek for Reader<S> {
     fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
-        self.cursor.seek(pos)
+        self.position = self.stream.seek(pos)?;
+
+        Ok(self.position)
     }
 }
 
+pub trait ArchiveReader: ReaderExt + Seek {
+    fn position(&self) -> u64;
+
+    fn object_padding(&self) -> ObjectPadding;
+
+    fn raw_fallback(&self) -> bool;
+
+    fn partial_recovery(&self) -> bool;
+
+    fn raw_blobs(&self) -> bool;
+
+    fn record_offsets(&self) -> bool;
+
+    fn blob_kind(&self, class_path: &str) -> Option<PersistenceBlobKind>;
+
+    fn save_version(&self) -> SaveVersion;
+
+    fn set_save_version(&mut self, save_version: SaveVersion);
+
+    fn absolute_position(&self) -> u64;
+
+    fn depth(&self) -> usize;
+
+    fn set_depth(&mut self, depth: usize);
+
+    fn len(&self) -> u64;
+
+    fn is_empty(&self) -> bool;
+
+    fn seek_checked(&mut self, offset: u64) -> anyhow::Result<()>;
+
+    // nested archives are always read from an in-memory copy of their bytes
+    fn sub_reader(&self, data: Vec<u8>, object_padding: ObjectPadding) -> Reader;
+}
+
+impl<S: Read + Seek> ArchiveReader for Reader<S> {
+    fn position(&self) -> u64 {
+        self.position
+    }
+
+    fn object_padding(&self) -> ObjectPadding {
+        self.object_padding
+    }
+
+    fn raw_fallback(&self) -> bool {
+        self.raw_fallback
+    }
+
+    fn partial_recovery(&self) -> bool {
+        self.partial_recovery
+    }
+
+    fn raw_blobs(&self) -> bool {
+        self.raw_blobs
+    }
+
+    fn record_offsets(&self) -> bool {
+        self.record_offsets
+    }
+
+    fn blob_kind(&self, class_path: &str) -> Option<PersistenceBlobKind> {
+        self.blob_kinds.iter()
+            .find(|(path, _)| path == class_path)
+            .map(|(_, kind)| *kind)
+            .or_else(|| PersistenceBlobKind::of(class_path))
+    }
+
+    fn save_version(&self) -> SaveVersion {
+        self.save_version
+    }
+
+    fn set_save_version(&mut self, save_version: SaveVersion) {
+        self.save_version = save_version;
+    }
+
+    fn absolute_position(&self) -> u64 {
+        Reader::absolute_position(self)
+    }
+
+    fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+    }
+
+    fn len(&self) -> u64 {
+        Reader::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        Reader::is_empty(self)
+    }
+
+    fn seek_checked(&mut self, offset: u64) -> anyhow::Result<()> {
+        Reader::seek_checked(self, offset)
+    }
+
+    fn sub_reader(&self, data: Vec<u8>, object_padding: ObjectPadding) -> Reader {
+        Reader::sub_reader(self, data, object_padding)
+    }
+}
+
+// reads a value that can contain further values one level deeper, the level is restored
+// on errors too, since raw_fallback and partial_recovery carry on after them
+pub fn nested<R: ArchiveReader, T>(reader: &mut R, f: impl FnOnce(&mut R) -> anyhow::Result<T>) -> anyhow::Result<T> {
+    let depth = reader.depth();
+
+    if depth >= MAX_NESTING_DEPTH {
+        bail!("Values are nested deeper than {} levels at offset {:#X}", MAX_NESTING_DEPTH, reader.absolute_position());
+    }
+
+    reader.set_depth(depth + 1);
+
+    let result = f(reader);
+
+    reader.set_depth(depth);
+
+    result
+}
+
 pub struct Writer {
     cursor: Cursor<Vec<u8>>,
-    pub object_padding: u32,
+    pub object_padding: ObjectPadding,
+    pub preserve_name_table: bool,
+    pub save_version: SaveVersion, // set from the header when an archive is written
 }
 
 impl Writer {
-    pub fn new(buf: Vec<u8>, object_padding: u32) -> Self {
+    pub fn new(buf: Vec<u8>, object_padding: ObjectPadding) -> Self {
         Self {
             cursor: Cursor::new(buf),
             object_padding,
+            preserve_name_table: false,
+            save_version: SaveVersion::default(),
+        }
+    }
+
+    // e.g. with the content_size of the save being rewritten, SavFile::write
+    // then also writes the archive to a buffer of this size before compressing it
+    pub fn with_capacity(capacity: usize, object_padding: ObjectPadding) -> Self {
+        Self::new(Vec::with_capacity(capacity), object_padding)
+    }
+
+    pub fn sub_writer(&self, object_padding: ObjectPadding) -> Self {
+        Self {
+            cursor: Cursor::new(Vec::new()),
+            object_padding,
+            preserve_name_table: self.preserve_name_table,
+            save_version: self.save_version,
         }
     }
 
+    pub fn capacity(&self) -> usize {
+        self.cursor.get_ref().capacity()
+    }
+
     pub fn get_ref(&self) -> &Vec<u8> {
         self.cursor.get_ref()
     }
@@ -78,39 +353,141 @@ impl Seek for Writer {
     }
 }
 
+pub trait ArchiveWriter: WriterExt + Seek {
+    fn position(&self) -> u64;
+
+    fn object_padding(&self) -> ObjectPadding;
+
+    fn preserve_name_table(&self) -> bool;
+
+    fn save_version(&self) -> SaveVersion;
+
+    fn set_save_version(&mut self, save_version: SaveVersion);
+
+    // nested archives are always written to an in-memory buffer first
+    fn sub_writer(&self, object_padding: ObjectPadding) -> Writer;
+}
+
+impl ArchiveWriter for Writer {
+    fn position(&self) -> u64 {
+        self.cursor.position()
+    }
+
+    fn object_padding(&self) -> ObjectPadding {
+        self.object_padding
+    }
+
+    fn preserve_name_table(&self) -> bool {
+        self.preserve_name_table
+    }
+
+    fn save_version(&self) -> SaveVersion {
+        self.save_version
+    }
+
+    fn set_save_version(&mut self, save_version: SaveVersion) {
+        self.save_version = save_version;
+    }
+
+    fn sub_writer(&self, object_padding: ObjectPadding) -> Writer {
+        Writer::sub_writer(self, object_padding)
+    }
+}
+
 pub trait ReaderExt: Read {
+    fn remaining(&self) -> u64;
+
+    // lengths read from the data are checked against what is left before allocating for
+    // them, so a corrupt length fails instead of allocating gigabytes
+    fn check_length(&self, length: u64) -> anyhow::Result<usize> {
+        if length > self.remaining() {
+            bail!("Length {} is past the end of the data ({} bytes left)", length, self.remaining());
+        }
+
+        Ok(length as usize)
+    }
+
+    // every element takes at least a byte, so counts are capped the same way
+    fn capacity_for(&self, count: u32) -> usize {
+        (count as u64).min(self.remaining()) as usize
+    }
+
     fn read_fstring(&mut self) -> anyhow::Result<String> {
-        let length = self.read_u32::<LittleEndian>()?;
+        let length = self.read_i32::<Endian>()?;
 
         if length == 0 {
             return Ok("".to_owned());
         }
 
-        let mut buf = vec![0; length as usize];
+        // a negative length means the string is stored as UTF-16
+        if length < 0 {
+            let length = self.check_length(length.unsigned_abs() as u64 * 2)? / 2;
+            let mut buf = vec![0u16; length];
+
+            self.read_u16_into::<Endian>(&mut buf)?;
+
+            if buf.pop() != Some(0) {
+                bail!("Wide FString is not null terminated");
+            }
+
+            return Ok(String::from_utf16(&buf)?);
+        }
+
+        let mut buf = vec![0; self.check_length(length as u64)?];
 
         self.read_exact(&mut buf)?;
 
-        Ok(CString::from_vec_with_nul(buf)?.to_str()?.to_owned())
+        if buf.pop() != Some(0) {
+            bail!("FString is not null terminated");
+        }
+
+        // the narrow form is Latin-1, not UTF-8
+        Ok(buf.into_iter().map(char::from).collect())
     }
 }
 
 pub trait WriterExt: Write {
     fn write_fstring(&mut self, value: String) -> anyhow::Result<()> {
         if value.is_empty() {
-            self.write_u32::<LittleEndian>(0)?;
+            self.write_u32::<Endian>(0)?;
 
             return Ok(());
         }
 
-        let c_string = CString::new(value)?;
-        let bytes = c_string.as_bytes_with_nul();
+        // the game would cut the string short in either encoding
+        if value.contains('\0') {
+            bail!("FString {:?} contains an interior NUL", value);
+        }
+
+        // the narrow form is Latin-1, anything past U+00FF needs UTF-16
+        if value.chars().any(|c| c as u32 > 0xFF) {
+            let mut buf = value.encode_utf16().collect::<Vec<_>>();
+
+            buf.push(0);
 
-        self.write_u32::<LittleEndian>(bytes.len() as u32)?;
-        self.write_all(bytes)?;
+            self.write_i32::<Endian>(-(buf.len() as i32))?;
+
+            for c in buf {
+                self.write_u16::<Endian>(c)?;
+            }
+
+            return Ok(());
+        }
+
+        let mut bytes = value.chars().map(|c| c as u8).collect::<Vec<_>>();
+
+        bytes.push(0);
+
+        self.write_u32::<Endian>(bytes.len() as u32)?;
+        self.write_all(&bytes)?;
 
         Ok(())
     }
 }
 
-impl ReaderExt for Reader {}
+impl<S: Read> ReaderExt for Reader<S> {
+    fn remaining(&self) -> u64 {
+        self.len.saturating_sub(self.position)
+    }
+}
 impl WriterExt for Writer {}
@@ -0,0 +1,14 @@
+pub mod io;
+pub mod properties;
+pub mod sav;
+pub mod structs;
+pub mod components;
+pub mod loadout;
+pub mod inventory;
+pub mod uesave;
+pub mod scrub;
+
+pub use crate::io::{ObjectPadding, Reader, Writer};
+pub use crate::properties::{Property, PropertyData};
+pub use crate::sav::{Compressor, ParseError, SavFile, SaveGameArchive, SaveGameArchiveContent, SaveSummary, SaveVersion, UObject};
+pub use crate::structs::{DynamicStruct, FGuid, FName, StructData};
@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::inventory::{item_name, EQUIPMENT_SLOT_PROPERTY, INVENTORY_ITEMS_PROPERTY, ITEM_BLUEPRINT_PROPERTY};
+use crate::properties::PropertyData;
+use crate::sav::{SaveGameArchive, SaveGameArchiveContent, UObject};
+use crate::structs::FName;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Loadout {
+    pub slots: BTreeMap<i32, FName>,
+}
+
+impl Loadout {
+    pub fn read(archive: &SaveGameArchive) -> anyhow::Result<Loadout> {
+        let mut loadout = Loadout::default();
+
+        for content in archive.content.archives() {
+            for object in &content.object_index {
+                loadout.read_inventory(content, object)?;
+            }
+        }
+
+        Ok(loadout)
+    }
+
+    fn read_inventory(
+        &mut self,
+        content: &SaveGameArchiveContent,
+        object: &UObject,
+    ) -> anyhow::Result<()> {
+        let items = match object.get_path(INVENTORY_ITEMS_PROPERTY)? {
+            Some(PropertyData::Array(items)) => items,
+            _ => return Ok(()),
+        };
+
+        for i in 0..items.elements.len() {
+            let slot = object.get_path(&format!("{}[{}].{}", INVENTORY_ITEMS_PROPERTY, i, EQUIPMENT_SLOT_PROPERTY))?;
+            let item = object.get_path(&format!("{}[{}].{}", INVENTORY_ITEMS_PROPERTY, i, ITEM_BLUEPRINT_PROPERTY))?;
+
+            let slot = match slot {
+                Some(PropertyData::Int32(slot)) if *slot >= 0 => *slot,
+                _ => continue, // not equipped
+            };
+
+            let item = match item {
+                Some(PropertyData::Object(index)) => content.resolve_object(*index),
+                _ => None,
+            };
+
+            // empty slots are left out of the loadout
+            if let Some(item) = item {
+                self.slots.insert(slot, item_name(&item.object_path));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,21 +1,150 @@
-use io::Reader;
-use std::fs;
-use std::path::PathBuf;
-use crate::sav::SavFile;
-
-mod io;
-mod properties;
-mod sav;
-mod structs;
-mod components;
+use std::{env, fs};
+use std::path::{Path, PathBuf};
+use remnant_save_parser::{Compressor, ObjectPadding, Reader, SavFile, SaveGameArchive, Writer};
+use remnant_save_parser::scrub::Scrubber;
 
 fn main() -> anyhow::Result<()> {
-    parse_all_in(".")?;
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    match args.first().map(String::as_str) {
+        Some("repack") => {
+            let verify = !args.iter().any(|arg| arg == "--no-verify");
+            let preserve_names = args.iter().any(|arg| arg == "--preserve-names");
+            let paths = args[1..].iter()
+                .filter(|arg| !arg.starts_with("--"))
+                .map(PathBuf::from)
+                .collect::<Vec<_>>();
+
+            let (input, output) = match paths.as_slice() {
+                [input] => (input, sav_path(input)?),
+                [input, output] => (input, output.clone()),
+                _ => anyhow::bail!("Usage: repack <input.json> [<output.sav>] [--no-verify] [--preserve-names]"),
+            };
+
+            println!("Repacking {:?}", input);
+
+            repack(input, &output, verify, preserve_names)?;
+        }
+        Some("diff") => match (args.get(1), args.get(2)) {
+            (Some(old), Some(new)) => diff(&PathBuf::from(old), &PathBuf::from(new))?,
+            _ => anyhow::bail!("Usage: diff <old.sav> <new.sav>"),
+        },
+        Some("info") => match args.get(1) {
+            Some(input) => info(&PathBuf::from(input))?,
+            _ => anyhow::bail!("Usage: info <file>"),
+        },
+        Some("get") => match (args.get(1), args.get(2)) {
+            (Some(input), Some(path)) => get(&PathBuf::from(input), path)?,
+            _ => anyhow::bail!("Usage: get <file> <path>"),
+        },
+        Some("scrub") => {
+            let mut scrubber = Scrubber::default();
+            let mut inputs = Vec::new();
+            let mut args = args[1..].iter();
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--field" => scrubber.properties.push(next_value(&mut args, arg)?),
+                    "--only" => scrubber.properties.clear(),
+                    _ => inputs.push(PathBuf::from(arg)),
+                }
+            }
+
+            for input in inputs {
+                println!("Scrubbing {:?}", input);
+
+                scrub(&input, &scrubber, "scrubbed.sav")?;
+            }
+        }
+        _ => {
+            let mut dir = ".".to_owned();
+            let mut output_dir = ".".to_owned();
+            let mut pretty = true;
+            let mut raw = false;
+            let mut offsets = false;
+            let mut raw_fallback = false;
+            let mut verify = true;
+            let mut anonymize = false;
+            let mut stats = false;
+            let mut tree = None;
+            let mut paths = Vec::new();
+            let mut args = args.iter();
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--dir" => dir = next_value(&mut args, arg)?,
+                    "--out" => output_dir = next_value(&mut args, arg)?,
+                    "--pretty" => pretty = true,
+                    "--compact" => pretty = false,
+                    "--raw" => raw = true,
+                    "--offsets" => offsets = true,
+                    "--raw-fallback" => raw_fallback = true,
+                    "--no-verify" => verify = false,
+                    "--anonymize" => anonymize = true,
+                    "--stats" => stats = true,
+                    "--tree" => tree = Some(usize::MAX),
+                    "--depth" => tree = Some(next_value(&mut args, arg)?.parse()?),
+                    _ => paths.push(PathBuf::from(arg)),
+                }
+            }
+
+            let extension = if raw { "bin" } else { "json" };
+
+            if let Some(max_depth) = tree {
+                return match paths.as_slice() {
+                    [input] => print_tree(input, max_depth),
+                    _ => anyhow::bail!("Usage: <input.sav> --tree [--depth <depth>]"),
+                };
+            }
+
+            if stats {
+                for input in &paths {
+                    println!("{:?}\n{}", input, read_archive(input)?.summary());
+                }
+
+                return Ok(());
+            }
+
+            if anonymize {
+                for input in &paths {
+                    println!("Anonymizing {:?}", input);
+
+                    scrub(input, &Scrubber::anonymizer(), "anonymized.sav")?;
+                }
+
+                return Ok(());
+            }
+
+            match paths.as_slice() {
+                [] => parse_all_in(&dir, &output_dir, pretty, raw, offsets, raw_fallback, verify)?,
+                [input] => unpack(input, &output_path(input, &output_dir, extension)?, pretty, raw, offsets, raw_fallback, verify)?,
+                [input, output] => unpack(input, output, pretty, raw, offsets, raw_fallback, verify)?,
+                _ => anyhow::bail!(
+                    "Usage: [<input.sav> [<output.json>]] [--dir <dir>] [--out <dir>] [--pretty | --compact | --raw] [--offsets] [--raw-fallback] [--no-verify]"
+                ),
+            }
+        }
+    }
 
     Ok(())
 }
 
-fn parse_all_in(dir: &str) -> anyhow::Result<()> {
+fn next_value<'a>(args: &mut impl Iterator<Item = &'a String>, flag: &str) -> anyhow::Result<String> {
+    args.next()
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("{} needs a value", flag))
+}
+
+fn parse_all_in(
+    dir: &str,
+    output_dir: &str,
+    pretty: bool,
+    raw: bool,
+    offsets: bool,
+    raw_fallback: bool,
+    verify: bool,
+) -> anyhow::Result<()> {
+    let output_extension = if raw { "bin" } else { "json" };
     let dir = fs::read_dir(dir)?;
 
     for entry in dir {
@@ -27,7 +156,7 @@ fn parse_all_in(dir: &str) -> anyhow::Result<()> {
                 if extension == "sav" {
                     println!("Parsing {:?}", path);
 
-                    unpack(&path, ".")?;
+                    unpack(&path, &output_path(&path, output_dir, output_extension)?, pretty, raw, offsets, raw_fallback, verify)?;
                 }
             }
         }
@@ -36,28 +165,187 @@ fn parse_all_in(dir: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn unpack(input_file: &PathBuf, output_dir: &str) -> anyhow::Result<()> {
+// `<output_dir>/<file name>.<extension>`
+fn output_path(input_file: &Path, output_dir: &str, extension: &str) -> anyhow::Result<PathBuf> {
     let file_name = input_file.file_name()
         .and_then(|x| x.to_str())
         .ok_or_else(|| anyhow::anyhow!("Invalid file name"))?;
 
+    let mut output_file = PathBuf::from(output_dir);
+
+    output_file.push(format!("{}.{}", file_name, extension));
+
+    Ok(output_file)
+}
+
+fn unpack(
+    input_file: &PathBuf,
+    output_file: &PathBuf,
+    pretty: bool,
+    raw: bool,
+    offsets: bool,
+    raw_fallback: bool,
+    verify: bool,
+) -> anyhow::Result<()> {
     // parse binary file
 
     let input_bytes = fs::read(input_file)?;
-    let mut reader = Reader::new(input_bytes, 4);
+    let mut reader = Reader::new(input_bytes, ObjectPadding::Four);
 
     let sav_file = SavFile::read(&mut reader)?;
-    let archive = sav_file.get_archive()?;
+    let content = if verify {
+        sav_file.get_content()?
+    } else {
+        sav_file.get_content_unchecked()?
+    };
 
-    let json = serde_json::to_vec_pretty(&archive)?;
+    // write the decompressed archive without parsing it
 
-    // write json file
+    if raw {
+        fs::write(output_file, content)?;
 
-    let mut output_file = PathBuf::from(output_dir);
+        return Ok(());
+    }
+
+    // offsets are positions in the decompressed archive, i.e. the `--raw` output
+
+    let mut reader = Reader::new(content, ObjectPadding::Four);
 
-    output_file.push(format!("{}.json", file_name));
+    reader.record_offsets = offsets;
+    reader.raw_fallback = raw_fallback;
+
+    let archive = SaveGameArchive::read(&mut reader)?;
+
+    let json = if pretty {
+        serde_json::to_vec_pretty(&archive)?
+    } else {
+        serde_json::to_vec(&archive)?
+    };
+
+    // write json file
 
     fs::write(output_file, json)?;
 
     Ok(())
 }
+
+// `<name>.sav.json` -> `<name>.sav` next to the input
+fn sav_path(input_file: &Path) -> anyhow::Result<PathBuf> {
+    let file_name = input_file.file_name()
+        .and_then(|x| x.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid file name"))?;
+
+    Ok(input_file.with_file_name(file_name.strip_suffix(".json").unwrap_or(file_name)))
+}
+
+fn repack(input_file: &PathBuf, output_file: &PathBuf, verify: bool, preserve_names: bool) -> anyhow::Result<()> {
+    // parse json file
+
+    let input_bytes = fs::read(input_file)?;
+    let archive: SaveGameArchive = serde_json::from_slice(&input_bytes)?;
+
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+    writer.preserve_name_table = preserve_names;
+
+    // raw properties point into the original name table
+    if archive.has_raw_properties() && !preserve_names {
+        println!("[WARN] {} has raw properties, keeping its original name table", input_file.display());
+        writer.preserve_name_table = true;
+    }
+
+    if verify {
+        SavFile::write_verified(&mut writer, &archive, &Compressor::Zlib)?;
+    } else {
+        SavFile::write(&mut writer, &archive, &Compressor::Zlib)?;
+    }
+
+    // write sav file
+
+    fs::write(output_file, writer.into_inner())?;
+
+    Ok(())
+}
+
+// prints the save version and game build as JSON, e.g. for save managers
+fn info(input_file: &PathBuf) -> anyhow::Result<()> {
+    let input_bytes = fs::read(input_file)?;
+    let mut reader = Reader::new(input_bytes, ObjectPadding::Four);
+
+    let header = SavFile::read(&mut reader)?.read_header()?;
+
+    println!("{}", serde_json::to_string(&header)?);
+
+    Ok(())
+}
+
+// prints a single property of the root object, e.g. `get profile.sav Characters[0]`
+fn get(input_file: &PathBuf, path: &str) -> anyhow::Result<()> {
+    let input_bytes = fs::read(input_file)?;
+    let mut reader = Reader::new(input_bytes, ObjectPadding::Four);
+
+    let sav_file = SavFile::read(&mut reader)?;
+    let archive = sav_file.get_archive()?;
+
+    let data = archive.root()
+        .map(|root| root.get_path(path))
+        .transpose()?
+        .flatten()
+        .ok_or_else(|| anyhow::anyhow!("Path not found: {}", path))?;
+
+    println!("{}", serde_json::to_string_pretty(data)?);
+
+    Ok(())
+}
+
+fn read_archive(input_file: &PathBuf) -> anyhow::Result<SaveGameArchive> {
+    let input_bytes = fs::read(input_file)?;
+    let mut reader = Reader::new(input_bytes, ObjectPadding::Four);
+
+    SavFile::read(&mut reader)?.get_archive()
+}
+
+fn diff(old_file: &PathBuf, new_file: &PathBuf) -> anyhow::Result<()> {
+    let changes = read_archive(old_file)?.diff(&read_archive(new_file)?)?;
+
+    for change in &changes {
+        println!("{}", change);
+    }
+
+    println!("{} changes", changes.len());
+
+    Ok(())
+}
+
+fn print_tree(input_file: &PathBuf, max_depth: usize) -> anyhow::Result<()> {
+    let input_bytes = fs::read(input_file)?;
+    let mut reader = Reader::new(input_bytes, ObjectPadding::Four);
+
+    let sav_file = SavFile::read(&mut reader)?;
+    let archive = sav_file.get_archive()?;
+
+    archive.dump_tree(&mut std::io::stdout().lock(), max_depth)?;
+
+    Ok(())
+}
+
+// writes `<file>.<extension>` next to the input
+fn scrub(input_file: &PathBuf, scrubber: &Scrubber, extension: &str) -> anyhow::Result<()> {
+    let input_bytes = fs::read(input_file)?;
+    let mut reader = Reader::new(input_bytes, ObjectPadding::Four);
+
+    let sav_file = SavFile::read(&mut reader)?;
+    let mut archive = sav_file.get_archive()?;
+
+    let count = scrubber.scrub(&mut archive);
+
+    println!("Scrubbed {} properties", count);
+
+    let mut writer = Writer::with_capacity(sav_file.content_size as usize, ObjectPadding::Four);
+
+    SavFile::write_verified(&mut writer, &archive, sav_file.compressor())?;
+
+    fs::write(input_file.with_extension(extension), writer.into_inner())?;
+
+    Ok(())
+}
@@ -1,20 +1,38 @@
 mod reader;
 mod writer;
+pub mod path;
 
-use crate::properties::reader::{BytePropertyValue, TextPropertyData};
+pub use crate::properties::reader::{BytePropertyValue, FormatArgumentValue, NumberFormattingOptions, TextPropertyData};
 use crate::structs::{FGuid, FName, StructData};
+use anyhow::bail;
 use serde::{Deserialize, Serialize};
 
 const REMNANT_SAVE_GAME_PROFILE: &str = "/Game/_Core/Blueprints/Base/BP_RemnantSaveGameProfile";
 const REMNANT_SAVE_GAME: &str = "/Game/_Core/Blueprints/Base/BP_RemnantSaveGame";
 
+// what the PersistenceBlob structs of a save game class hold, Reader::blob_kinds adds more.
+// nested archives, which have no class, hold archives. blobs of classes that aren't listed
+// are read as archives as well and kept as StructData::RawBlob if that fails
+pub const PERSISTENCE_BLOB_KINDS: [(&str, PersistenceBlobKind); 2] = [
+    (REMNANT_SAVE_GAME_PROFILE, PersistenceBlobKind::Archive),
+    (REMNANT_SAVE_GAME, PersistenceBlobKind::Container),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersistenceBlobKind {
+    Archive,   // StructData::PersistenceBlob
+    Container, // StructData::PersistenceContainer
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Property {
     pub name: FName,
     pub index: u32,
     pub type_name: FName,
-    pub size: u32,
+    pub size: u32, // advisory, recomputed from the written data except for Raw properties
     pub data: PropertyData,
+    #[serde(rename = "__offset", default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>, // where the property tag starts in the decompressed archive, see Reader::record_offsets
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,6 +57,7 @@ pub enum PropertyData {
     Str(String),
     StructReference(FGuid),
     Text(TextProperty),
+    Raw(Vec<u8>), // tag head and body of a property that failed to parse
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -57,7 +76,7 @@ pub struct EnumProperty {
 pub struct MapProperty {
     pub key_type: FName,
     pub value_type: FName,
-    pub elements: Vec<(PropertyData, PropertyData)>,
+    pub elements: Vec<(PropertyData, PropertyData)>, // kept in stream order, the game relies on it
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -90,4 +109,89 @@ pub enum HeadData {
         guid: FGuid,
     },
     None
-}
\ No newline at end of file
+}
+
+// utility functions
+
+impl PersistenceBlobKind {
+    pub fn of(class_path: &str) -> Option<PersistenceBlobKind> {
+        PERSISTENCE_BLOB_KINDS.iter()
+            .find(|(path, _)| *path == class_path)
+            .map(|(_, kind)| *kind)
+    }
+}
+
+impl PropertyData {
+    // parses user-entered decimals straight to the property's width so they are
+    // rounded once to the nearest representable value, unedited floats keep their bits
+    pub fn set_decimal(&mut self, text: &str) -> anyhow::Result<()> {
+        match self {
+            PropertyData::Float(value) => *value = text.trim().parse()?,
+            PropertyData::Double(value) => *value = text.trim().parse()?,
+            _ => bail!("{:?} is not a float property", self),
+        }
+
+        Ok(())
+    }
+}
+
+// the element count and the struct array head size are derived on write,
+// so editing an array only has to keep its elements consistent with the head
+impl ArrayProperty {
+    pub fn push(&mut self, element: PropertyData) -> anyhow::Result<()> {
+        if let HeadData::Struct { struct_name, .. } = &self.head_data {
+            match &element {
+                PropertyData::Struct(property) if property.struct_name.value == struct_name.value => {}
+                _ => bail!("Element {:?} does not match the array struct {}", element, struct_name.value),
+            }
+        }
+
+        self.elements.push(element);
+
+        Ok(())
+    }
+
+    // new struct elements take their struct name and guid from the head
+    pub fn push_struct(&mut self, data: StructData) -> anyhow::Result<()> {
+        let (struct_name, guid) = match &self.head_data {
+            HeadData::Struct { struct_name, guid, .. } => (struct_name.clone(), guid.clone()),
+            HeadData::None => bail!("Array of {} is not a struct array", self.inner_type.value),
+        };
+
+        self.elements.push(PropertyData::Struct(StructProperty {
+            struct_name,
+            guid,
+            data,
+        }));
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) -> anyhow::Result<PropertyData> {
+        if index >= self.elements.len() {
+            bail!("Array index {} is out of range (len {})", index, self.elements.len());
+        }
+
+        Ok(self.elements.remove(index))
+    }
+}
+
+impl MapProperty {
+    pub fn name_entries(&self) -> impl Iterator<Item = anyhow::Result<(&FName, &PropertyData)>> {
+        let key_type = &self.key_type.value;
+
+        self.elements.iter().map(move |(key, value)| match key {
+            PropertyData::Name(key) if key_type == "NameProperty" => Ok((key, value)),
+            _ => bail!("Map key {:?} is not of the declared key type {}", key, key_type),
+        })
+    }
+
+    pub fn int_entries(&self) -> impl Iterator<Item = anyhow::Result<(i32, &PropertyData)>> {
+        let key_type = &self.key_type.value;
+
+        self.elements.iter().map(move |(key, value)| match key {
+            PropertyData::Int32(key) if key_type == "IntProperty" => Ok((*key, value)),
+            _ => bail!("Map key {:?} is not of the declared key type {}", key, key_type),
+        })
+    }
+}
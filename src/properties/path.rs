@@ -0,0 +1,214 @@
+
+use anyhow::bail;
+use crate::properties::{Property, PropertyData};
+use crate::structs::{FGuid, StructData};
+
+// paths look like `Inventory.Items[3].ItemBP` or `Stats[Kills]`,
+// where `[..]` is an array index or a map key
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    Field(String),
+    Key(String),
+}
+
+impl PathSegment {
+    pub fn parse(path: &str) -> anyhow::Result<Vec<PathSegment>> {
+        let mut segments = Vec::new();
+
+        for part in path.split('.') {
+            let (field, mut rest) = match part.find('[') {
+                Some(index) => part.split_at(index),
+                None => (part, ""),
+            };
+
+            if field.is_empty() {
+                bail!("Invalid path {}: empty field name", path);
+            }
+
+            segments.push(PathSegment::Field(field.to_owned()));
+
+            while !rest.is_empty() {
+                let end = match (rest.starts_with('['), rest.find(']')) {
+                    (true, Some(end)) => end,
+                    _ => bail!("Invalid path {}: unterminated key in {}", path, part),
+                };
+
+                segments.push(PathSegment::Key(rest[1..end].to_owned()));
+                rest = &rest[end + 1..];
+            }
+        }
+
+        Ok(segments)
+    }
+}
+
+pub fn get_path<'a>(properties: &'a [Property], path: &str) -> anyhow::Result<Option<&'a PropertyData>> {
+    let segments = PathSegment::parse(path)?;
+    let (first, rest) = segments.split_first().expect("path has at least one segment");
+
+    let mut current = match find_field(properties, first) {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+
+    for segment in rest {
+        current = match segment {
+            PathSegment::Field(_) => match struct_properties(current) {
+                Some(properties) => match find_field(properties, segment) {
+                    Some(data) => data,
+                    None => return Ok(None),
+                },
+                None => return Ok(None),
+            },
+            PathSegment::Key(key) => match current {
+                PropertyData::Array(array) => match key.parse::<usize>().ok().and_then(|i| array.elements.get(i)) {
+                    Some(data) => data,
+                    None => return Ok(None),
+                },
+                PropertyData::Map(map) => match map.elements.iter().find(|(k, _)| key_matches(k, key)) {
+                    Some((_, data)) => data,
+                    None => return Ok(None),
+                },
+                _ => return Ok(None),
+            },
+        };
+    }
+
+    Ok(Some(current))
+}
+
+pub fn get_path_mut<'a>(properties: &'a mut [Property], path: &str) -> anyhow::Result<Option<&'a mut PropertyData>> {
+    let segments = PathSegment::parse(path)?;
+    let (first, rest) = segments.split_first().expect("path has at least one segment");
+
+    let mut current = match find_field_mut(properties, first) {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+
+    for segment in rest {
+        current = match segment {
+            PathSegment::Field(_) => match struct_properties_mut(current) {
+                Some(properties) => match find_field_mut(properties, segment) {
+                    Some(data) => data,
+                    None => return Ok(None),
+                },
+                None => return Ok(None),
+            },
+            PathSegment::Key(key) => match current {
+                PropertyData::Array(array) => match key.parse::<usize>().ok().and_then(|i| array.elements.get_mut(i)) {
+                    Some(data) => data,
+                    None => return Ok(None),
+                },
+                PropertyData::Map(map) => match map.elements.iter_mut().find(|(k, _)| key_matches(k, key)) {
+                    Some((_, data)) => data,
+                    None => return Ok(None),
+                },
+                _ => return Ok(None),
+            },
+        };
+    }
+
+    Ok(Some(current))
+}
+
+// every StructReference in `properties` along with the path leading to it
+pub fn struct_references(properties: &[Property]) -> Vec<(String, &FGuid)> {
+    let mut references = Vec::new();
+
+    for property in properties {
+        collect_struct_references(&property.data, property.name.value.clone(), &mut references);
+    }
+
+    references
+}
+
+fn collect_struct_references<'a>(data: &'a PropertyData, path: String, references: &mut Vec<(String, &'a FGuid)>) {
+    match data {
+        PropertyData::StructReference(guid) => references.push((path, guid)),
+        PropertyData::Struct(property) => {
+            if let StructData::Dynamic(dynamic_struct) = &property.data {
+                for property in &dynamic_struct.properties {
+                    collect_struct_references(&property.data, format!("{}.{}", path, property.name.value), references);
+                }
+            }
+        }
+        PropertyData::Array(array) => {
+            for (i, element) in array.elements.iter().enumerate() {
+                collect_struct_references(element, format!("{}[{}]", path, i), references);
+            }
+        }
+        PropertyData::Map(map) => {
+            for (i, (key, value)) in map.elements.iter().enumerate() {
+                let path = format!("{}[{}]", path, key_string(key).unwrap_or_else(|| i.to_string()));
+
+                // struct keyed maps hold their references in the keys
+                collect_struct_references(key, path.clone(), references);
+                collect_struct_references(value, path, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn find_field<'a>(properties: &'a [Property], segment: &PathSegment) -> Option<&'a PropertyData> {
+    match segment {
+        PathSegment::Field(name) => properties.iter()
+            .find(|property| property.name.matches(name))
+            .map(|property| &property.data),
+        PathSegment::Key(_) => None,
+    }
+}
+
+fn find_field_mut<'a>(properties: &'a mut [Property], segment: &PathSegment) -> Option<&'a mut PropertyData> {
+    match segment {
+        PathSegment::Field(name) => properties.iter_mut()
+            .find(|property| property.name.matches(name))
+            .map(|property| &mut property.data),
+        PathSegment::Key(_) => None,
+    }
+}
+
+fn struct_properties(data: &PropertyData) -> Option<&[Property]> {
+    match data {
+        PropertyData::Struct(property) => match &property.data {
+            StructData::Dynamic(dynamic_struct) => Some(&dynamic_struct.properties),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn struct_properties_mut(data: &mut PropertyData) -> Option<&mut [Property]> {
+    match data {
+        PropertyData::Struct(property) => match &mut property.data {
+            StructData::Dynamic(dynamic_struct) => Some(&mut dynamic_struct.properties),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn key_matches(data: &PropertyData, key: &str) -> bool {
+    match data {
+        PropertyData::Name(name) => name.matches(key),
+        _ => key_string(data).is_some_and(|value| value == key),
+    }
+}
+
+pub(crate) fn key_string(data: &PropertyData) -> Option<String> {
+    match data {
+        PropertyData::Name(name) => Some(name.to_display_string()),
+        PropertyData::Str(value) => Some(value.clone()),
+        PropertyData::Enum(value) => Some(value.value.value.clone()),
+        PropertyData::Int16(value) => Some(value.to_string()),
+        PropertyData::Int32(value) => Some(value.to_string()),
+        PropertyData::Int64(value) => Some(value.to_string()),
+        PropertyData::UInt16(value) => Some(value.to_string()),
+        PropertyData::UInt32(value) => Some(value.to_string()),
+        PropertyData::UInt64(value) => Some(value.to_string()),
+        PropertyData::StructReference(guid) => Some(guid.to_string()),
+        _ => None,
+    }
+}
@@ -1,41 +1,42 @@
-use crate::io::{Reader, ReaderExt};
-use crate::properties::{ArrayProperty, ByteProperty, EnumProperty, HeadData, MapProperty, Property, PropertyData, REMNANT_SAVE_GAME, REMNANT_SAVE_GAME_PROFILE, StructProperty, TextProperty};
+use crate::io::{nested, ArchiveReader, Endian, ObjectPadding};
+use crate::properties::{ArrayProperty, ByteProperty, EnumProperty, HeadData, MapProperty, PersistenceBlobKind, Property, PropertyData, StructProperty, TextProperty};
 use crate::structs::{
-    DateTime, DynamicStruct, FGuid, FName, FVector, PersistenceBlob, PersistenceContainer,
+    DateTime, DynamicStruct, FBox, FBox2D, FGuid, FIntVector, FLinearColor, FName, FQuaternion, FRotator, FSphere, FVector, PersistenceBlob, PersistenceContainer,
     StructData, Timespan,
 };
-use anyhow::bail;
-use byteorder::{LittleEndian, ReadBytesExt};
+use anyhow::{bail, Context};
+use byteorder::ReadBytesExt;
 use serde::{Deserialize, Serialize};
-use std::io::Read;
+use std::io::SeekFrom;
 use crate::sav::SaveGameArchiveContent;
 
-pub trait PropertyReader {
+pub trait PropertyReader<R: ArchiveReader> {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         size: u32,
     ) -> anyhow::Result<PropertyData>;
 
     fn read_head(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData>;
 
     fn read_raw(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData>;
 }
 
 impl Property {
-    fn read(
-        reader: &mut Reader,
+    fn read<R: ArchiveReader>(
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<Option<Self>> {
+        let offset = reader.record_offsets().then(|| reader.absolute_position());
         let name = save_archive.read_name(reader)?;
 
         if name.value == "None" {
@@ -43,11 +44,38 @@ impl Property {
         }
 
         let type_name = save_archive.read_name(reader)?;
-        let size = reader.read_u32::<LittleEndian>()?;
-        let index = reader.read_u32::<LittleEndian>()?;
+        let size = reader.read_u32::<Endian>()?;
+        let index = reader.read_u32::<Endian>()?;
 
-        let mut property_parser = PropertyParser::from_name(reader, &type_name.value, false)?;
-        let data = property_parser.read(reader, save_archive, size)?;
+        let start_pos = reader.position();
+        let absolute_pos = reader.absolute_position();
+        let data = match Self::read_data(reader, save_archive, &type_name, size) {
+            Ok(data) => data,
+            Err(_) if reader.raw_fallback() => {
+                // the tag head is type specific, so it has to be skipped to find
+                // where the `size` bytes of the body end
+
+                reader.seek(SeekFrom::Start(start_pos))?;
+                PropertyParser::skip_head(reader, save_archive, &type_name.value)?;
+
+                let length = reader.position() - start_pos + size as u64;
+
+                reader.seek(SeekFrom::Start(start_pos))?;
+
+                let mut data = vec![0; reader.check_length(length)?];
+                reader.read_exact(&mut data)?;
+
+                PropertyData::Raw(data)
+            }
+            Err(err) => {
+                return Err(err.context(format!(
+                    "while parsing property {} ({}) at offset {:#X}",
+                    name.to_display_string(),
+                    type_name.value,
+                    absolute_pos,
+                )));
+            }
+        };
 
         let property = Property {
             name,
@@ -55,13 +83,25 @@ impl Property {
             type_name,
             size,
             data,
+            offset,
         };
 
         Ok(Some(property))
     }
 
-    pub fn read_multiple(
-        reader: &mut Reader,
+    fn read_data<R: ArchiveReader>(
+        reader: &mut R,
+        save_archive: &SaveGameArchiveContent,
+        type_name: &FName,
+        size: u32,
+    ) -> anyhow::Result<PropertyData> {
+        let mut property_parser = PropertyParser::from_name(reader, &type_name.value, false)?;
+
+        property_parser.read(reader, save_archive, size)
+    }
+
+    pub fn read_multiple<R: ArchiveReader>(
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<Vec<Property>> {
         let mut properties = Vec::new();
@@ -83,12 +123,15 @@ impl Property {
 pub struct PropertyParser;
 
 impl PropertyParser {
-    pub fn from_name(
-        reader: &Reader,
+    // `alt` is only set for map keys, where struct keys are stored as a bare guid
+    // referencing a persistence container actor (see MapStructPropertyParser),
+    // property tags, array elements and map values always use the regular parsers
+    pub fn from_name<R: ArchiveReader>(
+        reader: &R,
         name: &str,
         alt: bool,
-    ) -> anyhow::Result<Box<dyn PropertyReader>> {
-        let parser: Box<dyn PropertyReader> = match name {
+    ) -> anyhow::Result<Box<dyn PropertyReader<R>>> {
+        let parser: Box<dyn PropertyReader<R>> = match name {
             "ByteProperty" => Box::new(BytePropertyParser),
             "BoolProperty" => Box::new(BoolPropertyParser),
             "EnumProperty" => Box::new(EnumPropertyParser),
@@ -118,11 +161,39 @@ impl PropertyParser {
             }
             "StrProperty" => Box::new(StrPropertyParser),
             "TextProperty" => Box::new(TextPropertyParser),
-            _ => bail!("Unknown property type: {} at {}", name, reader.position()),
+            _ => bail!("Unknown property type: {} at offset {:#X}", name, reader.absolute_position()),
         };
 
         Ok(parser)
     }
+
+    pub fn skip_head<R: ArchiveReader>(
+        reader: &mut R,
+        save_archive: &SaveGameArchiveContent,
+        name: &str,
+    ) -> anyhow::Result<()> {
+        match name {
+            "StructProperty" => {
+                save_archive.read_name(reader)?;
+                FGuid::read(reader)?;
+            }
+            "ByteProperty" | "EnumProperty" | "ArrayProperty" | "SetProperty" => {
+                save_archive.read_name(reader)?;
+            }
+            "MapProperty" => {
+                save_archive.read_name(reader)?;
+                save_archive.read_name(reader)?;
+            }
+            "BoolProperty" => {
+                reader.read_u8()?;
+            }
+            _ => {}
+        }
+
+        reader.read_u8()?;
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -141,6 +212,42 @@ pub enum TextPropertyData {
     None {
         culture_invariant_string: Option<String>,
     },
+    ArgumentFormat {
+        source_format: Box<TextProperty>,
+        arguments: Vec<(String, FormatArgumentValue)>,
+    },
+    AsNumber {
+        source_value: FormatArgumentValue,
+        format_options: Option<NumberFormattingOptions>,
+        target_culture: String,
+    },
+    AsDate {
+        source_date_time: DateTime,
+        date_style: i8,
+        time_zone: String,
+        target_culture: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum FormatArgumentValue {
+    Int(i64),
+    UInt(u64),
+    Float(f32),
+    Double(f64),
+    Text(Box<TextProperty>),
+    Gender(u8),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NumberFormattingOptions {
+    pub always_sign: bool,
+    pub use_grouping: bool,
+    pub rounding_mode: i8,
+    pub minimum_integral_digits: i32,
+    pub maximum_integral_digits: i32,
+    pub minimum_fractional_digits: i32,
+    pub maximum_fractional_digits: i32,
 }
 
 pub struct BytePropertyParser;
@@ -160,10 +267,10 @@ pub struct StrPropertyParser;
 pub struct TextPropertyParser;
 pub struct MapStructPropertyParser;
 
-impl PropertyReader for BytePropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for BytePropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
@@ -182,7 +289,7 @@ impl PropertyReader for BytePropertyParser {
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
@@ -190,7 +297,7 @@ impl PropertyReader for BytePropertyParser {
 
     fn read_raw(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
         let value = reader.read_u8()?;
@@ -202,10 +309,10 @@ impl PropertyReader for BytePropertyParser {
     }
 }
 
-impl PropertyReader for BoolPropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for BoolPropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
@@ -218,7 +325,7 @@ impl PropertyReader for BoolPropertyParser {
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
@@ -226,7 +333,7 @@ impl PropertyReader for BoolPropertyParser {
 
     fn read_raw(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
         let value = reader.read_u8()?;
@@ -235,10 +342,10 @@ impl PropertyReader for BoolPropertyParser {
     }
 }
 
-impl PropertyReader for EnumPropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for EnumPropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
@@ -253,46 +360,63 @@ impl PropertyReader for EnumPropertyParser {
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
     }
 
+    // array and map elements only store the value, the enum name isn't known there
     fn read_raw(
         &mut self,
-        _reader: &mut Reader,
-        _save_archive: &SaveGameArchiveContent,
+        reader: &mut R,
+        save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
-        todo!("EnumPropertyParser::read_raw")
+        let value = save_archive.read_name(reader)?;
+
+        Ok(PropertyData::Enum(EnumProperty {
+            enum_name: FName::none(),
+            value,
+        }))
     }
 }
 
-impl PropertyReader for MapPropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for MapPropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
         let key_type = save_archive.read_name(reader)?;
         let value_type = save_archive.read_name(reader)?;
 
+        // struct values carry no head, so their struct name stays None and they are read
+        // as tagged property lists, which is how the game stores non-native structs in maps
         let mut key_parser = PropertyParser::from_name(reader, key_type.value.as_str(), true)?;
         let mut value_parser = PropertyParser::from_name(reader, value_type.value.as_str(), false)?;
 
         reader.read_u8()?;
-        reader.read_u32::<LittleEndian>()?;
+        reader.read_u32::<Endian>()?;
 
-        let element_count = reader.read_u32::<LittleEndian>()?;
-        let mut elements = Vec::with_capacity(element_count as usize);
+        let element_count = reader.read_u32::<Endian>()?;
+        let elements = nested(reader, |reader| {
+            let mut elements = Vec::with_capacity(reader.capacity_for(element_count));
 
-        for _ in 0..element_count {
-            let key = key_parser.read_raw(reader, save_archive)?;
-            let value = value_parser.read_raw(reader, save_archive)?;
+            for i in 0..element_count {
+                let start_pos = reader.absolute_position();
+                let key = key_parser.read_raw(reader, save_archive)
+                    .with_context(|| format!("while parsing Map key {} at offset {:#X}", i, start_pos))?;
 
-            elements.push((key, value));
-        }
+                let start_pos = reader.absolute_position();
+                let value = value_parser.read_raw(reader, save_archive)
+                    .with_context(|| format!("while parsing Map value {} at offset {:#X}", i, start_pos))?;
+
+                elements.push((key, value));
+            }
+
+            Ok(elements)
+        })?;
 
         Ok(PropertyData::Map(MapProperty {
             key_type,
@@ -303,7 +427,7 @@ impl PropertyReader for MapPropertyParser {
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
@@ -311,64 +435,88 @@ impl PropertyReader for MapPropertyParser {
 
     fn read_raw(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
-        todo!("MapPropertyParser::read_raw")
+        bail!("Maps nested in arrays or maps are not supported")
     }
 }
 
-impl PropertyReader for ArrayPropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for ArrayPropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
         let inner_type = save_archive.read_name(reader)?;
-        let mut inner_parser = PropertyParser::from_name(reader, inner_type.value.as_str(), false)?;
 
         reader.read_u8()?;
 
-        let element_count = reader.read_u32::<LittleEndian>()?;
-        let mut elements = Vec::with_capacity(element_count as usize);
-
-        let head_data = inner_parser.read_head(reader, save_archive)?;
-
-        for _ in 0..element_count {
-            let value = inner_parser.read_raw(reader, save_archive)?;
-
-            elements.push(value);
-        }
-
-        Ok(PropertyData::Array(ArrayProperty {
-            inner_type,
-            head_data,
-            elements,
-        }))
+        Self::read_elements(reader, save_archive, inner_type)
     }
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
     }
 
+    // arrays nested in arrays store their inner type without the tag's trailing byte
     fn read_raw(
         &mut self,
-        _reader: &mut Reader,
-        _save_archive: &SaveGameArchiveContent,
+        reader: &mut R,
+        save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
-        todo!("ArrayPropertyParser::read_raw")
+        let inner_type = save_archive.read_name(reader)?;
+
+        Self::read_elements(reader, save_archive, inner_type)
     }
 }
 
-impl PropertyReader for ObjectPropertyParser {
+impl ArrayPropertyParser {
+    fn read_elements<R: ArchiveReader>(
+        reader: &mut R,
+        save_archive: &SaveGameArchiveContent,
+        inner_type: FName,
+    ) -> anyhow::Result<PropertyData> {
+        nested(reader, |reader| Self::read_nested_elements(reader, save_archive, inner_type))
+    }
+
+    fn read_nested_elements<R: ArchiveReader>(
+        reader: &mut R,
+        save_archive: &SaveGameArchiveContent,
+        inner_type: FName,
+    ) -> anyhow::Result<PropertyData> {
+        let mut inner_parser = PropertyParser::from_name(reader, inner_type.value.as_str(), false)?;
+
+        let element_count = reader.read_u32::<Endian>()?;
+        let mut elements = Vec::with_capacity(reader.capacity_for(element_count));
+
+        let head_data = inner_parser.read_head(reader, save_archive)?;
+
+        for i in 0..element_count {
+            let start_pos = reader.absolute_position();
+            let value = inner_parser.read_raw(reader, save_archive)
+                .with_context(|| format!("while parsing Array element {} at offset {:#X}", i, start_pos))?;
+
+            elements.push(value);
+        }
+
+        Ok(PropertyData::Array(ArrayProperty {
+            inner_type,
+            head_data,
+            elements,
+        }))
+    }
+}
+
+impl<R: ArchiveReader> PropertyReader<R> for ObjectPropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
@@ -381,7 +529,7 @@ impl PropertyReader for ObjectPropertyParser {
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
@@ -389,32 +537,32 @@ impl PropertyReader for ObjectPropertyParser {
 
     fn read_raw(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
-        let class_name_index = reader.read_i32::<LittleEndian>()?;
+        let class_name_index = reader.read_i32::<Endian>()?;
 
         Ok(PropertyData::Object(class_name_index))
     }
 }
 
-impl PropertyReader for SoftObjectPropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for SoftObjectPropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
-        _save_archive: &SaveGameArchiveContent,
+        reader: &mut R,
+        save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
         reader.read_u8()?;
 
-        let class_name = reader.read_fstring()?;
+        let value = Self::read_raw(self, reader, save_archive)?;
 
-        Ok(PropertyData::SoftObject(class_name))
+        Ok(value)
     }
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
@@ -422,17 +570,19 @@ impl PropertyReader for SoftObjectPropertyParser {
 
     fn read_raw(
         &mut self,
-        _reader: &mut Reader,
+        reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
-        todo!("SoftObjectPropertyParser::read_raw")
+        let class_name = reader.read_fstring()?;
+
+        Ok(PropertyData::SoftObject(class_name))
     }
 }
 
-impl PropertyReader for NamePropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for NamePropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
@@ -445,7 +595,7 @@ impl PropertyReader for NamePropertyParser {
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
@@ -453,7 +603,7 @@ impl PropertyReader for NamePropertyParser {
 
     fn read_raw(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
         let value = save_archive.read_name(reader)?;
@@ -462,10 +612,10 @@ impl PropertyReader for NamePropertyParser {
     }
 }
 
-impl PropertyReader for StructPropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for StructPropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         size: u32,
     ) -> anyhow::Result<PropertyData> {
@@ -486,13 +636,13 @@ impl PropertyReader for StructPropertyParser {
 
     fn read_head(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         let name = save_archive.read_name(reader)?;
         let type_name = save_archive.read_name(reader)?;
-        self.size = reader.read_u32::<LittleEndian>()?;
-        let index = reader.read_u32::<LittleEndian>()?;
+        self.size = reader.read_u32::<Endian>()?;
+        let index = reader.read_u32::<Endian>()?;
         self.struct_name = save_archive.read_name(reader)?;
         self.guid = FGuid::read(reader)?;
 
@@ -509,7 +659,7 @@ impl PropertyReader for StructPropertyParser {
 
     fn read_raw(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
         let data = self.read_struct_data(reader, save_archive, self.size)?;
@@ -523,9 +673,9 @@ impl PropertyReader for StructPropertyParser {
 }
 
 impl StructPropertyParser {
-    fn read_struct_data(
+    fn read_struct_data<R: ArchiveReader>(
         &self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<StructData> {
@@ -533,44 +683,82 @@ impl StructPropertyParser {
             "SoftClassPath" => StructData::SoftClassPath(reader.read_fstring()?),
             "SoftObjectPath" => StructData::SoftObjectPath(reader.read_fstring()?),
             "PersistenceBlob" => {
-                let size = reader.read_u32::<LittleEndian>()?;
-                let mut data = vec![0; size as usize];
+                let size = reader.read_u32::<Endian>()?;
+                let mut data = vec![0; reader.check_length(size as u64)?];
 
+                let start_pos = reader.absolute_position();
                 reader.read_exact(&mut data)?;
 
-                let mut reader = Reader::new(data, 8);
+                if reader.raw_blobs() {
+                    return Ok(StructData::RawBlob(data));
+                }
 
-                if let Some(save_game_class_path) = &save_archive.save_game_class_path {
-                    match save_game_class_path.path.as_str() {
-                        REMNANT_SAVE_GAME_PROFILE => {
-                            StructData::PersistenceBlob(PersistenceBlob::read(&mut reader)?)
-                        }
-                        REMNANT_SAVE_GAME => {
-                            StructData::PersistenceContainer(PersistenceContainer::read(&mut reader)?)
-                        }
-                        _ => {
-                            bail!("Unknown SaveGameClassPath: {}", save_game_class_path.path);
+                let unknown_class = match &save_archive.save_game_class_path {
+                    Some(class_path) => reader.blob_kind(&class_path.path).is_none().then_some(&class_path.path),
+                    None => None,
+                };
+
+                if let Some(class_path) = unknown_class {
+                    println!(
+                        "[WARN] Unknown save game class {}, reading its persistence blob as an archive",
+                        class_path,
+                    );
+
+                    let mut blob_reader = reader.sub_reader(data.clone(), ObjectPadding::Eight);
+
+                    // the blob may hold something else entirely, its bytes are kept as they are then
+                    return match nested(&mut blob_reader, PersistenceBlob::read) {
+                        Ok(blob) => Ok(StructData::PersistenceBlob(blob)),
+                        Err(e) => {
+                            println!(
+                                "[WARN] Persistence blob at offset {:#X} is kept as raw bytes: {:#}",
+                                start_pos,
+                                e,
+                            );
+
+                            Ok(StructData::RawBlob(data))
                         }
-                    }
-                } else {
-                    bail!("SaveGameClassPath not found");
+                    };
                 }
+
+                // offsets within the blob are relative to its start, nested archives have no class
+                let kind = match &save_archive.save_game_class_path {
+                    Some(class_path) => reader.blob_kind(&class_path.path).unwrap_or(PersistenceBlobKind::Archive),
+                    None => PersistenceBlobKind::Archive,
+                };
+
+                let mut reader = reader.sub_reader(data, ObjectPadding::Eight);
+                let context = || format!("while parsing PersistenceBlob at offset {:#X}", start_pos);
+
+                let data = nested(&mut reader, |reader| match kind {
+                    PersistenceBlobKind::Archive => PersistenceBlob::read(reader).map(StructData::PersistenceBlob),
+                    PersistenceBlobKind::Container => PersistenceContainer::read(reader).map(StructData::PersistenceContainer),
+                });
+
+                data.with_context(context)?
             }
             "Guid" => StructData::Guid(FGuid::read(reader)?),
             "Timespan" => StructData::Timespan(Timespan::read(reader)?),
             "DateTime" => StructData::DateTime(DateTime::read(reader)?),
             "Vector" => StructData::Vector(FVector::read(reader)?),
-            _ => StructData::Dynamic(DynamicStruct::read(reader, save_archive)?),
+            "Rotator" => StructData::Rotator(FRotator::read(reader)?),
+            "Quat" => StructData::Quat(FQuaternion::read(reader)?),
+            "IntVector" => StructData::IntVector(FIntVector::read(reader)?),
+            "LinearColor" => StructData::LinearColor(FLinearColor::read(reader)?),
+            "Box" => StructData::Box(FBox::read(reader)?),
+            "Box2D" => StructData::Box2D(FBox2D::read(reader)?),
+            "Sphere" => StructData::Sphere(FSphere::read(reader)?),
+            _ => StructData::Dynamic(nested(reader, |reader| DynamicStruct::read(reader, save_archive))?),
         };
 
         Ok(data)
     }
 }
 
-impl PropertyReader for StrPropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for StrPropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
@@ -583,7 +771,7 @@ impl PropertyReader for StrPropertyParser {
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
@@ -591,7 +779,7 @@ impl PropertyReader for StrPropertyParser {
 
     fn read_raw(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
         let value = reader.read_fstring()?;
@@ -600,19 +788,19 @@ impl PropertyReader for StrPropertyParser {
     }
 }
 
-impl PropertyReader for MapStructPropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for MapStructPropertyParser {
     fn read(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
-        panic!("MapStructPropertyParser::read");
+        bail!("Struct references are only stored as map keys, not as tagged properties")
     }
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
@@ -620,7 +808,7 @@ impl PropertyReader for MapStructPropertyParser {
 
     fn read_raw(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
         let value = FGuid::read(reader)?;
@@ -629,10 +817,10 @@ impl PropertyReader for MapStructPropertyParser {
     }
 }
 
-impl PropertyReader for TextPropertyParser {
+impl<R: ArchiveReader> PropertyReader<R> for TextPropertyParser {
     fn read(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
         _size: u32,
     ) -> anyhow::Result<PropertyData> {
@@ -645,7 +833,7 @@ impl PropertyReader for TextPropertyParser {
 
     fn read_head(
         &mut self,
-        _reader: &mut Reader,
+        _reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<HeadData> {
         Ok(HeadData::None)
@@ -653,10 +841,23 @@ impl PropertyReader for TextPropertyParser {
 
     fn read_raw(
         &mut self,
-        reader: &mut Reader,
+        reader: &mut R,
         _save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<PropertyData> {
-        let flags = reader.read_u32::<LittleEndian>()?;
+        let text = TextProperty::read(reader)?;
+
+        Ok(PropertyData::Text(text))
+    }
+}
+
+impl TextProperty {
+    // argument formats nest further texts, so this is limited like arrays and structs
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<TextProperty> {
+        nested(reader, Self::read_history)
+    }
+
+    fn read_history<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<TextProperty> {
+        let flags = reader.read_u32::<Endian>()?;
         let history_type = reader.read_u8()?;
 
         let data = match history_type {
@@ -672,9 +873,60 @@ impl PropertyReader for TextPropertyParser {
                     source_string,
                 }
             }
+            3 => {
+                // ArgumentFormat
+                let source_format = Box::new(TextProperty::read(reader)?);
+                let count = reader.read_u32::<Endian>()?;
+                let mut arguments = Vec::with_capacity(reader.capacity_for(count));
+
+                for _ in 0..count {
+                    let name = reader.read_fstring()?;
+                    let value = FormatArgumentValue::read(reader)?;
+
+                    arguments.push((name, value));
+                }
+
+                TextPropertyData::ArgumentFormat {
+                    source_format,
+                    arguments,
+                }
+            }
+            4 => {
+                // AsNumber
+                let source_value = FormatArgumentValue::read(reader)?;
+                let has_format_options = reader.read_u32::<Endian>()? != 0;
+
+                let format_options = if has_format_options {
+                    Some(NumberFormattingOptions::read(reader)?)
+                } else {
+                    None
+                };
+
+                let target_culture = reader.read_fstring()?;
+
+                TextPropertyData::AsNumber {
+                    source_value,
+                    format_options,
+                    target_culture,
+                }
+            }
+            7 => {
+                // AsDate
+                let source_date_time = DateTime::read(reader)?;
+                let date_style = reader.read_i8()?;
+                let time_zone = reader.read_fstring()?;
+                let target_culture = reader.read_fstring()?;
+
+                TextPropertyData::AsDate {
+                    source_date_time,
+                    date_style,
+                    time_zone,
+                    target_culture,
+                }
+            }
             255 => {
                 // None
-                let has_culture_invariant_string = reader.read_u32::<LittleEndian>()? != 0;
+                let has_culture_invariant_string = reader.read_u32::<Endian>()? != 0;
 
                 let culture_invariant_string = if has_culture_invariant_string {
                     Some(reader.read_fstring()?)
@@ -686,10 +938,42 @@ impl PropertyReader for TextPropertyParser {
                     culture_invariant_string,
                 }
             }
-            _ => bail!("Unsupported history type: {}", history_type),
+            _ => bail!("Unsupported text history type: {}", history_type),
         };
 
-        Ok(PropertyData::Text(TextProperty { flags, data }))
+        Ok(TextProperty { flags, data })
+    }
+}
+
+impl FormatArgumentValue {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<FormatArgumentValue> {
+        let value_type = reader.read_u8()?;
+
+        let value = match value_type {
+            0 => FormatArgumentValue::Int(reader.read_i64::<Endian>()?),
+            1 => FormatArgumentValue::UInt(reader.read_u64::<Endian>()?),
+            2 => FormatArgumentValue::Float(reader.read_f32::<Endian>()?),
+            3 => FormatArgumentValue::Double(reader.read_f64::<Endian>()?),
+            4 => FormatArgumentValue::Text(Box::new(TextProperty::read(reader)?)),
+            5 => FormatArgumentValue::Gender(reader.read_u8()?),
+            _ => bail!("Unsupported format argument type: {}", value_type),
+        };
+
+        Ok(value)
+    }
+}
+
+impl NumberFormattingOptions {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<NumberFormattingOptions> {
+        Ok(NumberFormattingOptions {
+            always_sign: reader.read_u32::<Endian>()? != 0,
+            use_grouping: reader.read_u32::<Endian>()? != 0,
+            rounding_mode: reader.read_i8()?,
+            minimum_integral_digits: reader.read_i32::<Endian>()?,
+            maximum_integral_digits: reader.read_i32::<Endian>()?,
+            minimum_fractional_digits: reader.read_i32::<Endian>()?,
+            maximum_fractional_digits: reader.read_i32::<Endian>()?,
+        })
     }
 }
 
@@ -699,10 +983,10 @@ macro_rules! impl_primitive_parser {
     ) => {
         pub struct $name;
 
-        impl PropertyReader for $name {
+        impl<R: ArchiveReader> PropertyReader<R> for $name {
             fn read(
                 &mut self,
-                reader: &mut Reader,
+                reader: &mut R,
                 save_archive: &SaveGameArchiveContent,
                 _size: u32,
             ) -> anyhow::Result<PropertyData> {
@@ -715,7 +999,7 @@ macro_rules! impl_primitive_parser {
 
             fn read_head(
                 &mut self,
-                _reader: &mut Reader,
+                _reader: &mut R,
                 _save_archive: &SaveGameArchiveContent,
             ) -> anyhow::Result<HeadData> {
                 Ok(HeadData::None)
@@ -723,10 +1007,10 @@ macro_rules! impl_primitive_parser {
 
             fn read_raw(
                 &mut self,
-                reader: &mut Reader,
+                reader: &mut R,
                 _save_archive: &SaveGameArchiveContent,
             ) -> anyhow::Result<PropertyData> {
-                let value = reader.$read_method::<LittleEndian>()?;
+                let value = reader.$read_method::<Endian>()?;
 
                 Ok(PropertyData::$prop_data_name(value))
             }
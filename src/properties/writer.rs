@@ -1,50 +1,64 @@
-use std::io::{Seek, SeekFrom, Write};
-use crate::io::{Writer, WriterExt};
-use crate::properties::reader::{BytePropertyValue, TextPropertyData};
+use std::io::SeekFrom;
+use crate::io::{ArchiveWriter, Endian, ObjectPadding};
+use crate::properties::reader::{BytePropertyValue, FormatArgumentValue, NumberFormattingOptions, TextPropertyData};
 use crate::properties::{ArrayProperty, ByteProperty, EnumProperty, HeadData, MapProperty, Property, PropertyData, StructProperty, TextProperty};
 use crate::structs::{FGuid, FName, StructData};
 use anyhow::bail;
-use byteorder::{LittleEndian, WriteBytesExt};
+use byteorder::WriteBytesExt;
 use crate::sav::NameTable;
 
 pub trait PropertyWriter<T> {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &T,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32>;
 
-    fn write_raw(
-        writer: &mut Writer,
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
         data: &T,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32>;
 }
 
 impl Property {
-    pub fn write(
+    pub fn write<W: ArchiveWriter>(
         &self,
-        writer: &mut Writer,
+        writer: &mut W,
         name_table: &mut NameTable,
     ) -> anyhow::Result<()> {
         name_table.write_name(writer, &self.name)?;
         name_table.write_name(writer, &self.type_name)?;
-        writer.write_u32::<LittleEndian>(0)?; // placeholder for size
-        writer.write_u32::<LittleEndian>(self.index)?;
+        writer.write_u32::<Endian>(0)?; // placeholder for size
+        writer.write_u32::<Endian>(self.index)?;
 
         let start_pos = writer.position();
-        let size = PropertyComposer::write(writer, &self.data, name_table)?;
+        let size = if let PropertyData::Raw(data) = &self.data {
+            // raw data holds name indices of the original save, which a rebuilt name table doesn't keep
+            if !writer.preserve_name_table() {
+                bail!(
+                    "Raw property {} can only be written with the original name table (preserve_name_table)",
+                    self.name.to_display_string(),
+                );
+            }
+
+            // raw data can't be measured, so the size read from the save is reused
+            writer.write_all(data)?;
+            self.size
+        } else {
+            PropertyComposer::write(writer, &self.data, name_table)?
+        };
         let end_pos = writer.position();
         
         writer.seek(SeekFrom::Start(start_pos - 8))?;
-        writer.write_u32::<LittleEndian>(size)?;
+        writer.write_u32::<Endian>(size)?;
         writer.seek(SeekFrom::Start(end_pos))?;
 
         Ok(())
     }
 
-    pub fn write_none(
-        writer: &mut Writer,
+    pub fn write_none<W: ArchiveWriter>(
+        writer: &mut W,
         name_table: &mut NameTable,
     ) -> anyhow::Result<()> {
         name_table.write_name(writer, &FName::from("None"))?;
@@ -56,8 +70,8 @@ impl Property {
 pub struct PropertyComposer;
 
 impl PropertyComposer {
-    pub fn write(
-        writer: &mut Writer,
+    pub fn write<W: ArchiveWriter>(
+        writer: &mut W,
         property_data: &PropertyData,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -122,13 +136,16 @@ impl PropertyComposer {
             PropertyData::StructReference(property_data) => {
                 MapStructPropertyWriter::write(writer, property_data, name_table)?
             }
+            PropertyData::Raw(_) => {
+                bail!("Raw property data can only be written by Property::write")
+            }
         };
 
         Ok(size)
     }
 
-    pub fn write_raw(
-        writer: &mut Writer,
+    pub fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
         property_data: &PropertyData,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -193,6 +210,9 @@ impl PropertyComposer {
             PropertyData::StructReference(property_data) => {
                 MapStructPropertyWriter::write_raw(writer, property_data, name_table)?
             }
+            PropertyData::Raw(_) => {
+                bail!("Raw property data can only be written by Property::write")
+            }
         };
 
         Ok(size)
@@ -213,8 +233,8 @@ pub struct TextPropertyWriter;
 pub struct MapStructPropertyWriter;
 
 impl PropertyWriter<ByteProperty> for BytePropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &ByteProperty,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -235,8 +255,8 @@ impl PropertyWriter<ByteProperty> for BytePropertyWriter {
         Ok(size)
     }
 
-    fn write_raw(
-        writer: &mut Writer,
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
         data: &ByteProperty,
         _name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -251,8 +271,8 @@ impl PropertyWriter<ByteProperty> for BytePropertyWriter {
 }
 
 impl PropertyWriter<bool> for BoolPropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &bool,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -262,8 +282,8 @@ impl PropertyWriter<bool> for BoolPropertyWriter {
         Ok(0)
     }
 
-    fn write_raw(
-        writer: &mut Writer,
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
         data: &bool,
         _name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -274,8 +294,8 @@ impl PropertyWriter<bool> for BoolPropertyWriter {
 }
 
 impl PropertyWriter<EnumProperty> for EnumPropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &EnumProperty,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -290,18 +310,22 @@ impl PropertyWriter<EnumProperty> for EnumPropertyWriter {
         }
     }
 
-    fn write_raw(
-        _writer: &mut Writer,
-        _data: &EnumProperty,
-        _name_table: &mut NameTable,
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
+        data: &EnumProperty,
+        name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
-        todo!("EnumPropertyWriter::write_raw")
+        let start_pos = writer.position();
+
+        name_table.write_name(writer, &data.value)?;
+
+        Ok((writer.position() - start_pos) as u32)
     }
 }
 
 impl PropertyWriter<MapProperty> for MapPropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &MapProperty,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -309,41 +333,76 @@ impl PropertyWriter<MapProperty> for MapPropertyWriter {
         name_table.write_name(writer, &data.value_type)?;
 
         writer.write_u8(0)?;
-        writer.write_u32::<LittleEndian>(0)?;
+        writer.write_u32::<Endian>(0)?;
 
-        writer.write_u32::<LittleEndian>(data.elements.len() as u32)?;
+        writer.write_u32::<Endian>(data.elements.len() as u32)?;
 
         let mut size = 8;
 
         for (key, value) in &data.elements {
-            size += PropertyComposer::write_raw(writer, key, name_table)?;
+            // struct keys are read as bare guids (see PropertyParser::from_name), so they're written as such
+            size += match (data.key_type.value.as_str(), key) {
+                ("StructProperty", PropertyData::StructReference(guid)) => {
+                    MapStructPropertyWriter::write_raw(writer, guid, name_table)?
+                }
+                ("StructProperty", _) => bail!("Struct map keys have to be struct references, got {:?}", key),
+                _ => PropertyComposer::write_raw(writer, key, name_table)?,
+            };
             size += PropertyComposer::write_raw(writer, value, name_table)?;
         }
 
         Ok(size)
     }
 
-    fn write_raw(
-        _writer: &mut Writer,
+    fn write_raw<W: ArchiveWriter>(
+        _writer: &mut W,
         _data: &MapProperty,
         _name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
-        todo!("MapPropertyWriter::write_raw")
+        bail!("Maps nested in arrays or maps are not supported")
     }
 }
 
 impl PropertyWriter<ArrayProperty> for ArrayPropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &ArrayProperty,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
         name_table.write_name(writer, &data.inner_type)?;
         writer.write_u8(0)?;
-        writer.write_u32::<LittleEndian>(data.elements.len() as u32)?;
-        
+
+        let start_pos = writer.position();
+
+        Self::write_elements(writer, data, name_table)?;
+
+        Ok((writer.position() - start_pos) as u32)
+    }
+
+    // see ArrayPropertyParser::read_raw
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
+        data: &ArrayProperty,
+        name_table: &mut NameTable,
+    ) -> anyhow::Result<u32> {
+        let start_pos = writer.position();
+
+        name_table.write_name(writer, &data.inner_type)?;
+        Self::write_elements(writer, data, name_table)?;
+
+        Ok((writer.position() - start_pos) as u32)
+    }
+}
+
+impl ArrayPropertyWriter {
+    fn write_elements<W: ArchiveWriter>(
+        writer: &mut W,
+        data: &ArrayProperty,
+        name_table: &mut NameTable,
+    ) -> anyhow::Result<()> {
+        writer.write_u32::<Endian>(data.elements.len() as u32)?;
+
         let mut size_pos = 0;
-        let mut size = 4;
 
         match &data.head_data {
             HeadData::Struct {
@@ -356,48 +415,37 @@ impl PropertyWriter<ArrayProperty> for ArrayPropertyWriter {
                 name_table.write_name(writer, name)?;
                 name_table.write_name(writer, type_name)?;
                 size_pos = writer.position();
-                writer.write_u32::<LittleEndian>(0)?; // placeholder for size
-                writer.write_u32::<LittleEndian>(*index)?;
+                writer.write_u32::<Endian>(0)?; // placeholder for size
+                writer.write_u32::<Endian>(*index)?;
                 name_table.write_name(writer, struct_name)?;
                 guid.write(writer)?;
                 writer.write_u8(0)?;
-
-                size += 2 + 2 + 4 + 4 + 2 + 16 + 1;
             }
             HeadData::None => {}
         }
 
-        let mut content_size = 0;
-        
+        let content_start_pos = writer.position();
+
         for element in &data.elements {
-            content_size += PropertyComposer::write_raw(writer, element, name_table)?;
+            PropertyComposer::write_raw(writer, element, name_table)?;
         }
 
-        size += content_size;
+        let end_pos = writer.position();
 
+        // the head size only covers the element data, not the head itself
         if let HeadData::Struct { .. } = &data.head_data {
-            let end_pos = writer.position();
-
             writer.seek(SeekFrom::Start(size_pos))?;
-            writer.write_u32::<LittleEndian>(size)?;
+            writer.write_u32::<Endian>((end_pos - content_start_pos) as u32)?;
             writer.seek(SeekFrom::Start(end_pos))?;
         }
 
-        Ok(size)
-    }
-
-    fn write_raw(
-        _writer: &mut Writer,
-        _data: &ArrayProperty,
-        _name_table: &mut NameTable,
-    ) -> anyhow::Result<u32> {
-        todo!("ArrayPropertyWriter::write_raw")
+        Ok(())
     }
 }
 
 impl PropertyWriter<i32> for ObjectPropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &i32,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -407,41 +455,47 @@ impl PropertyWriter<i32> for ObjectPropertyWriter {
         Ok(4)
     }
 
-    fn write_raw(
-        writer: &mut Writer,
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
         data: &i32,
         _name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
-        writer.write_i32::<LittleEndian>(*data)?;
+        writer.write_i32::<Endian>(*data)?;
 
         Ok(4)
     }
 }
 
 impl PropertyWriter<String> for SoftObjectPropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &String,
-        _name_table: &mut NameTable,
+        name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
         writer.write_u8(0)?;
-        writer.write_fstring(data.clone())?;
 
-        Ok(4 + data.len() as u32 + 1)
+        let size = Self::write_raw(writer, data, name_table)?;
+
+        Ok(size)
     }
 
-    fn write_raw(
-        _writer: &mut Writer,
-        _data: &String,
+    // measured, since non-ASCII strings are stored as UTF-16 and empty ones without a terminator
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
+        data: &String,
         _name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
-        todo!("SoftObjectPropertyWriter::write_raw")
+        let start_pos = writer.position();
+
+        writer.write_fstring(data.clone())?;
+
+        Ok((writer.position() - start_pos) as u32)
     }
 }
 
 impl PropertyWriter<FName> for NamePropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &FName,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -451,8 +505,8 @@ impl PropertyWriter<FName> for NamePropertyWriter {
         Ok(2)
     }
 
-    fn write_raw(
-        writer: &mut Writer,
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
         data: &FName,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -463,39 +517,43 @@ impl PropertyWriter<FName> for NamePropertyWriter {
 }
 
 impl PropertyWriter<String> for StrPropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &String,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
         writer.write_u8(0)?;
-        Self::write_raw(writer, data, name_table)?;
 
-        Ok(4 + data.len() as u32 + 1)
+        let size = Self::write_raw(writer, data, name_table)?;
+
+        Ok(size)
     }
 
-    fn write_raw(
-        writer: &mut Writer,
+    // measured, since non-ASCII strings are stored as UTF-16 and empty ones without a terminator
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
         data: &String,
         _name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
+        let start_pos = writer.position();
+
         writer.write_fstring(data.clone())?;
 
-        Ok(4 + data.len() as u32 + 1)
+        Ok((writer.position() - start_pos) as u32)
     }
 }
 
 impl PropertyWriter<FGuid> for MapStructPropertyWriter {
-    fn write(
-        _writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        _writer: &mut W,
         _data: &FGuid,
         _name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
-        panic!("MapStructPropertyWriter::write")
+        bail!("Struct references can only be written as map entries, not as tagged properties")
     }
 
-    fn write_raw(
-        writer: &mut Writer,
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
         data: &FGuid,
         _name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -506,8 +564,8 @@ impl PropertyWriter<FGuid> for MapStructPropertyWriter {
 }
 
 impl PropertyWriter<TextProperty> for TextPropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &TextProperty,
         _name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -517,14 +575,24 @@ impl PropertyWriter<TextProperty> for TextPropertyWriter {
         Ok(size)
     }
 
-    fn write_raw(
-        writer: &mut Writer,
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
         data: &TextProperty,
         _name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
-        writer.write_u32::<LittleEndian>(data.flags)?;
+        let start = writer.position();
+
+        data.write(writer)?;
+
+        Ok((writer.position() - start) as u32)
+    }
+}
 
-        let size = match &data.data {
+impl TextProperty {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u32::<Endian>(self.flags)?;
+
+        match &self.data {
             TextPropertyData::Base {
                 namespace,
                 key,
@@ -534,8 +602,51 @@ impl PropertyWriter<TextProperty> for TextPropertyWriter {
                 writer.write_fstring(namespace.clone())?;
                 writer.write_fstring(key.clone())?;
                 writer.write_fstring(source_string.clone())?;
+            }
+            TextPropertyData::ArgumentFormat {
+                source_format,
+                arguments,
+            } => {
+                writer.write_u8(3)?;
+                source_format.write(writer)?;
+                writer.write_u32::<Endian>(arguments.len() as u32)?;
 
-                5 + (4 + namespace.len() as u32 + 1) + (4 + key.len() as u32 + 1) + (4 + source_string.len() as u32 + 1)
+                for (name, value) in arguments {
+                    writer.write_fstring(name.clone())?;
+                    value.write(writer)?;
+                }
+            }
+            TextPropertyData::AsNumber {
+                source_value,
+                format_options,
+                target_culture,
+            } => {
+                writer.write_u8(4)?;
+                source_value.write(writer)?;
+
+                match format_options {
+                    Some(format_options) => {
+                        writer.write_u32::<Endian>(1)?;
+                        format_options.write(writer)?;
+                    }
+                    None => {
+                        writer.write_u32::<Endian>(0)?;
+                    }
+                }
+
+                writer.write_fstring(target_culture.clone())?;
+            }
+            TextPropertyData::AsDate {
+                source_date_time,
+                date_style,
+                time_zone,
+                target_culture,
+            } => {
+                writer.write_u8(7)?;
+                source_date_time.write(writer)?;
+                writer.write_i8(*date_style)?;
+                writer.write_fstring(time_zone.clone())?;
+                writer.write_fstring(target_culture.clone())?;
             }
             TextPropertyData::None {
                 culture_invariant_string,
@@ -544,19 +655,64 @@ impl PropertyWriter<TextProperty> for TextPropertyWriter {
 
                 match culture_invariant_string {
                     Some(culture_invariant_string) => {
-                        writer.write_u32::<LittleEndian>(1)?;
+                        writer.write_u32::<Endian>(1)?;
                         writer.write_fstring(culture_invariant_string.clone())?;
-                        5 + 4 + (4 + culture_invariant_string.len() as u32 + 1)
                     }
                     None => {
-                        writer.write_u32::<LittleEndian>(0)?;
-                        5 + 4
+                        writer.write_u32::<Endian>(0)?;
                     }
                 }
             }
-        };
+        }
 
-        Ok(size)
+        Ok(())
+    }
+}
+
+impl FormatArgumentValue {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        match self {
+            FormatArgumentValue::Int(value) => {
+                writer.write_u8(0)?;
+                writer.write_i64::<Endian>(*value)?;
+            }
+            FormatArgumentValue::UInt(value) => {
+                writer.write_u8(1)?;
+                writer.write_u64::<Endian>(*value)?;
+            }
+            FormatArgumentValue::Float(value) => {
+                writer.write_u8(2)?;
+                writer.write_f32::<Endian>(*value)?;
+            }
+            FormatArgumentValue::Double(value) => {
+                writer.write_u8(3)?;
+                writer.write_f64::<Endian>(*value)?;
+            }
+            FormatArgumentValue::Text(value) => {
+                writer.write_u8(4)?;
+                value.write(writer)?;
+            }
+            FormatArgumentValue::Gender(value) => {
+                writer.write_u8(5)?;
+                writer.write_u8(*value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl NumberFormattingOptions {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u32::<Endian>(self.always_sign as u32)?;
+        writer.write_u32::<Endian>(self.use_grouping as u32)?;
+        writer.write_i8(self.rounding_mode)?;
+        writer.write_i32::<Endian>(self.minimum_integral_digits)?;
+        writer.write_i32::<Endian>(self.maximum_integral_digits)?;
+        writer.write_i32::<Endian>(self.minimum_fractional_digits)?;
+        writer.write_i32::<Endian>(self.maximum_fractional_digits)?;
+
+        Ok(())
     }
 }
 
@@ -567,8 +723,8 @@ macro_rules! impl_property_writer {
         pub struct $name;
 
         impl PropertyWriter<$prop_data_type> for $name {
-            fn write(
-                writer: &mut Writer,
+            fn write<W: ArchiveWriter>(
+                writer: &mut W,
                 data: &$prop_data_type,
                 name_table: &mut NameTable,
             ) -> anyhow::Result<u32> {
@@ -578,12 +734,12 @@ macro_rules! impl_property_writer {
                 Ok($size)
             }
 
-            fn write_raw(
-                writer: &mut Writer,
+            fn write_raw<W: ArchiveWriter>(
+                writer: &mut W,
                 data: &$prop_data_type,
                 _name_table: &mut NameTable,
             ) -> anyhow::Result<u32> {
-                writer.$write_method::<LittleEndian>(*data)?;
+                writer.$write_method::<Endian>(*data)?;
 
                 Ok($size)
             }
@@ -601,8 +757,8 @@ impl_property_writer!(FloatPropertyWriter, write_f32, f32, 4);
 impl_property_writer!(DoublePropertyWriter, write_f64, f64, 8);
 
 impl PropertyWriter<StructProperty> for StructPropertyWriter {
-    fn write(
-        writer: &mut Writer,
+    fn write<W: ArchiveWriter>(
+        writer: &mut W,
         data: &StructProperty,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -616,8 +772,8 @@ impl PropertyWriter<StructProperty> for StructPropertyWriter {
         Ok(size)
     }
 
-    fn write_raw(
-        writer: &mut Writer,
+    fn write_raw<W: ArchiveWriter>(
+        writer: &mut W,
         data: &StructProperty,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -628,8 +784,8 @@ impl PropertyWriter<StructProperty> for StructPropertyWriter {
 }
 
 impl StructPropertyWriter {
-    fn write_struct_data(
-        writer: &mut Writer,
+    fn write_struct_data<W: ArchiveWriter>(
+        writer: &mut W,
         data: &StructProperty,
         name_table: &mut NameTable,
     ) -> anyhow::Result<u32> {
@@ -654,31 +810,54 @@ impl StructPropertyWriter {
             StructData::Vector(vector) => {
                 vector.write(writer)?;
             }
+            StructData::Rotator(rotator) => {
+                rotator.write(writer)?;
+            }
+            StructData::Quat(quat) => {
+                quat.write(writer)?;
+            }
+            StructData::IntVector(vector) => {
+                vector.write(writer)?;
+            }
+            StructData::LinearColor(color) => {
+                color.write(writer)?;
+            }
+            StructData::Box(bounds) => {
+                bounds.write(writer)?;
+            }
+            StructData::Box2D(bounds) => {
+                bounds.write(writer)?;
+            }
+            StructData::Sphere(sphere) => {
+                sphere.write(writer)?;
+            }
             StructData::Dynamic(dynamic_struct) => {
                 dynamic_struct.write(writer, name_table)?;
             }
             StructData::PersistenceBlob(persistence_blob) => {
-                let buf = Vec::new();
-                let mut blob_writer = Writer::new(buf, 8);
+                let mut blob_writer = writer.sub_writer(ObjectPadding::Eight);
 
                 persistence_blob.write(&mut blob_writer)?;
 
                 let buf = blob_writer.into_inner();
 
-                writer.write_u32::<LittleEndian>(buf.len() as u32)?;
+                writer.write_u32::<Endian>(buf.len() as u32)?;
                 writer.write_all(&buf)?;
             }
             StructData::PersistenceContainer(persistence_container) => {
-                let buf = Vec::new();
-                let mut blob_writer = Writer::new(buf, 8);
+                let mut blob_writer = writer.sub_writer(ObjectPadding::Eight);
 
                 persistence_container.write(&mut blob_writer)?;
 
                 let buf = blob_writer.into_inner();
 
-                writer.write_u32::<LittleEndian>(buf.len() as u32)?;
+                writer.write_u32::<Endian>(buf.len() as u32)?;
                 writer.write_all(&buf)?;
             }
+            StructData::RawBlob(data) => {
+                writer.write_u32::<Endian>(data.len() as u32)?;
+                writer.write_all(data)?;
+            }
         }
 
         let end_pos = writer.position();
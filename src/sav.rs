@@ -1,12 +1,26 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use anyhow::bail;
 use serde::{Deserialize, Serialize};
 use crate::components::Component;
+use crate::io::ObjectPadding;
 use crate::properties::Property;
-use crate::structs::{FName, FPackageVersion, FTopLevelAssetPath};
+use crate::structs::{Actor, DateTime, FGuid, FName, FPackageVersion, FTopLevelAssetPath, StructData};
+use crate::properties::{path, ArrayProperty, PropertyData, StructProperty};
 
 mod reader;
 mod writer;
+mod tree;
+mod diff;
+mod visit;
+mod summary;
 
 const ARCHIVE_V2_HEADER_TAG: u64 = 0x22222222_9E2A83C1;
+const ARCHIVE_V2_HEADER_TAG_SWAPPED: u64 = 0xC1832A9E_22222222; // written by big-endian platforms
+const SAVE_DATE_TIME_PROPERTY: &str = "SaveDateTime";
+const CHARACTER_NAME_PROPERTY: &str = "CharacterName";
+const CHUNK_SIZE: u64 = 2 << 16; // uncompressed size of every chunk but the last
+const MAX_CHUNK_SIZE: u64 = 64 * CHUNK_SIZE; // well past anything the game writes, only bounds allocations
 
 #[derive(Debug)]
 pub enum Compressor {
@@ -40,6 +54,17 @@ pub struct SavChunk {
     pub compressed_data: Vec<u8>,
 }
 
+// layouts of the save format, detected once when the header is read and handed to the readers and
+// writers (see ArchiveReader::save_version), version specific reading and writing goes through this.
+// format changes within a file version can be told apart by build number.
+// known good: file version 9, which every release so far uses. the default is the newest known
+// layout, for archives that are read or written without a header
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SaveVersion {
+    #[default]
+    V9,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SaveGameArchiveHeader {
     pub save_game_file_version: u32,
@@ -59,11 +84,15 @@ pub struct SaveGameArchiveContent {
     pub name_table: NameTable,
     pub object_index: Vec<UObject>,
     pub version: u32,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool, // set when recovering a truncated save or skipping objects, such archives can't be written
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NameTable {
     pub list: Vec<String>,
+    #[serde(skip)]
+    lookup: HashMap<String, u16>, // first index of each name in `list`, empty for deserialized tables
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -73,7 +102,13 @@ pub struct UObject {
     pub object_path: String,
     pub loaded_data: Option<UObjectLoadedData>,
     pub properties: Vec<Property>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub empty_property_list: bool, // the object has a property list without properties, i.e. only its terminator
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trailing_data: Vec<u8>, // bytes after the properties that aren't understood yet, kept for repacking
     pub components: Option<Vec<Component>>, // Some if is actor
+    #[serde(rename = "__offset", default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>, // where the object's data starts in the decompressed archive, see Reader::record_offsets
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -82,3 +117,459 @@ pub struct UObjectLoadedData {
     pub outer_id: u32,
 }
 
+// an object that was skipped by a lenient read, `offset` is where its data starts
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub object_id: u32,
+    pub offset: u64,
+    pub message: String,
+}
+
+// paths are `<object path>:<property path>`, objects of nested archives
+// are appended with ` > `, e.g. `<object>:Container[<actor id>] > <object>:Quantity`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ArchiveChange {
+    Added { path: String, value: serde_json::Value },
+    Removed { path: String, value: serde_json::Value },
+    Changed { path: String, old: serde_json::Value, new: serde_json::Value },
+}
+
+// counts over this and all nested archives, except `names` which is the top-level name table
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SaveSummary {
+    pub archives: usize,
+    pub objects: usize,
+    pub components: BTreeMap<String, usize>,
+    pub actors: usize,
+    pub names: usize,
+    pub properties: usize,
+}
+
+// utility functions
+
+impl SaveVersion {
+    pub const KNOWN: [SaveVersion; 1] = [SaveVersion::V9];
+
+    pub fn detect(header: &SaveGameArchiveHeader) -> anyhow::Result<SaveVersion> {
+        match header.save_game_file_version {
+            9 => Ok(SaveVersion::V9),
+            version => bail!(
+                "Unsupported save game file version: {} (build {}, supported: {})",
+                version,
+                header.build_number,
+                SaveVersion::KNOWN.map(|known| known.file_version().to_string()).join(", "),
+            ),
+        }
+    }
+
+    pub fn file_version(self) -> u32 {
+        match self {
+            SaveVersion::V9 => 9,
+        }
+    }
+
+    // zero bytes after the property list of an object, only object 0 uses the archive's padding
+    pub fn terminator_size(self, object_padding: ObjectPadding, object_id: u32) -> usize {
+        match (self, object_id) {
+            (SaveVersion::V9, 0) => object_padding.terminator_size(),
+            (SaveVersion::V9, _) => ObjectPadding::Four.terminator_size(),
+        }
+    }
+
+    // objects that weren't loaded store their name and outer object after their path
+    pub fn has_loaded_data(self, was_loaded: bool) -> bool {
+        match self {
+            SaveVersion::V9 => !was_loaded,
+        }
+    }
+}
+
+impl SavFile {
+    // saves use one compressor for all chunks
+    pub fn compressor(&self) -> &Compressor {
+        self.chunks.first().map_or(&Compressor::Zlib, |chunk| &chunk.compressor)
+    }
+
+    // byte ranges of the decompressed content that differ from `other`,
+    // including any bytes one of them has past the end of the other
+    pub fn content_delta(&self, other: &SavFile) -> anyhow::Result<Vec<Range<usize>>> {
+        let content = self.get_content()?;
+        let other_content = other.get_content()?;
+
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+
+        for (i, (a, b)) in content.iter().zip(&other_content).enumerate() {
+            if a == b {
+                continue;
+            }
+
+            match ranges.last_mut() {
+                Some(range) if range.end == i => range.end += 1,
+                _ => ranges.push(i..i + 1),
+            }
+        }
+
+        let common = content.len().min(other_content.len());
+        let longest = content.len().max(other_content.len());
+
+        if common < longest {
+            match ranges.last_mut() {
+                Some(range) if range.end == common => range.end = longest,
+                _ => ranges.push(common..longest),
+            }
+        }
+
+        Ok(ranges)
+    }
+
+    // byte ranges of the decompressed content stored in each chunk. the chunks hold the content
+    // past the crc32 and content size, so the first one starts at 8, and are compressed on their
+    // own, so a chunk not overlapping content_delta can be re-used as is, see SavFile::write_reusing
+    pub fn chunk_boundaries(&self) -> Vec<Range<usize>> {
+        let mut start = 8;
+
+        self.chunks.iter()
+            .map(|chunk| {
+                let end = start + chunk.compression_info.uncompressed_size as usize;
+                let range = start..end;
+
+                start = end;
+
+                range
+            })
+            .collect()
+    }
+}
+
+impl UObject {
+    pub fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|property| property.name.matches(name))
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Property> {
+        self.properties.iter_mut().find(|property| property.name.matches(name))
+    }
+
+    pub fn get_int(&self, name: &str) -> Option<i32> {
+        match self.get(name)?.data {
+            PropertyData::Int32(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)?.data {
+            PropertyData::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        match &self.get(name)?.data {
+            PropertyData::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_struct(&self, name: &str) -> Option<&StructProperty> {
+        match &self.get(name)?.data {
+            PropertyData::Struct(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_array(&self, name: &str) -> Option<&ArrayProperty> {
+        match &self.get(name)?.data {
+            PropertyData::Array(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn set_int(&mut self, name: &str, value: i32) -> anyhow::Result<()> {
+        match &mut self.get_mut(name).ok_or_else(|| anyhow::anyhow!("Object has no {} property", name))?.data {
+            PropertyData::Int32(data) => *data = value,
+            data => bail!("{} is not an int property: {:?}", name, data),
+        }
+
+        Ok(())
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) -> anyhow::Result<()> {
+        match &mut self.get_mut(name).ok_or_else(|| anyhow::anyhow!("Object has no {} property", name))?.data {
+            PropertyData::Bool(data) => *data = value,
+            data => bail!("{} is not a bool property: {:?}", name, data),
+        }
+
+        Ok(())
+    }
+
+    pub fn set_str(&mut self, name: &str, value: &str) -> anyhow::Result<()> {
+        match &mut self.get_mut(name).ok_or_else(|| anyhow::anyhow!("Object has no {} property", name))?.data {
+            PropertyData::Str(data) => *data = value.to_owned(),
+            data => bail!("{} is not a str property: {:?}", name, data),
+        }
+
+        Ok(())
+    }
+
+    pub fn get_path(&self, path: &str) -> anyhow::Result<Option<&PropertyData>> {
+        path::get_path(&self.properties, path)
+    }
+
+    pub fn get_path_mut(&mut self, path: &str) -> anyhow::Result<Option<&mut PropertyData>> {
+        path::get_path_mut(&mut self.properties, path)
+    }
+
+    // strings may be stored either as StrProperty or NameProperty
+    pub fn get_string(&self, path: &str) -> anyhow::Result<Option<&str>> {
+        match self.get_path(path)? {
+            Some(PropertyData::Str(value)) => Ok(Some(value)),
+            Some(PropertyData::Name(name)) => Ok(Some(&name.value)),
+            Some(data) => bail!("{} is not a string property: {:?}", path, data),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_string(&mut self, path: &str, value: &str) -> anyhow::Result<()> {
+        match self.get_path_mut(path)? {
+            Some(PropertyData::Str(data)) => *data = value.to_owned(),
+            Some(PropertyData::Name(name)) => *name = FName::from(value),
+            Some(data) => bail!("{} is not a string property: {:?}", path, data),
+            None => bail!("Object has no {} property", path),
+        }
+
+        Ok(())
+    }
+}
+
+impl SaveGameArchiveContent {
+    pub fn resolve_object(&self, index: i32) -> Option<&UObject> {
+        usize::try_from(index).ok().and_then(|index| self.object_index.get(index))
+    }
+
+    // every StructReference as (object path, property path, guid) across this and all nested archives
+    pub fn struct_references(&self) -> Vec<(&str, String, &FGuid)> {
+        self.archives()
+            .into_iter()
+            .flat_map(|archive| &archive.object_index)
+            .flat_map(|object| {
+                path::struct_references(&object.properties)
+                    .into_iter()
+                    .map(|(path, guid)| (object.object_path.as_str(), path, guid))
+            })
+            .collect()
+    }
+
+    // actors are keyed by their unique id, so a guid is matched against the
+    // Guid struct properties of each actor's root object
+    pub fn resolve_actor(&self, guid: &FGuid) -> Option<&Actor> {
+        self.actors().into_iter().find(|actor| {
+            actor.archive.object_index.first().is_some_and(|root| {
+                root.properties.iter().any(|property| match &property.data {
+                    PropertyData::Struct(property) => matches!(&property.data, StructData::Guid(value) if value == guid),
+                    _ => false,
+                })
+            })
+        })
+    }
+
+    // all actors of the persistence containers in this and all nested archives, sorted by id within each container
+    pub fn actors(&self) -> Vec<&Actor> {
+        let mut actors = Vec::new();
+
+        for archive in self.archives() {
+            for object in &archive.object_index {
+                for property in &object.properties {
+                    if let PropertyData::Struct(property) = &property.data {
+                        if let StructData::PersistenceContainer(container) = &property.data {
+                            let mut sorted = container.actors.iter().collect::<Vec<_>>();
+
+                            sorted.sort_by_key(|(unique_id, _)| **unique_id);
+                            actors.extend(sorted.into_iter().map(|(_, actor)| actor));
+                        }
+                    }
+                }
+            }
+        }
+
+        actors
+    }
+
+    // this archive followed by all archives nested in its persistence blobs and containers
+    pub fn archives(&self) -> Vec<&SaveGameArchiveContent> {
+        let mut archives = vec![self];
+
+        for object in &self.object_index {
+            for property in &object.properties {
+                if let PropertyData::Struct(property) = &property.data {
+                    match &property.data {
+                        StructData::PersistenceBlob(blob) => {
+                            archives.extend(blob.archive.archives());
+                        }
+                        StructData::PersistenceContainer(container) => {
+                            let mut actors = container.actors.iter().collect::<Vec<_>>();
+
+                            actors.sort_by_key(|(unique_id, _)| **unique_id);
+
+                            for (_, actor) in actors {
+                                archives.extend(actor.archive.archives());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        archives
+    }
+
+    // mutable counterpart of archives(), calls `f` for each archive in the same order
+    pub fn for_each_archive_mut(
+        &mut self,
+        f: &mut impl FnMut(&mut SaveGameArchiveContent) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        f(self)?;
+
+        for object in &mut self.object_index {
+            for property in &mut object.properties {
+                if let PropertyData::Struct(property) = &mut property.data {
+                    match &mut property.data {
+                        StructData::PersistenceBlob(blob) => {
+                            blob.archive.for_each_archive_mut(f)?;
+                        }
+                        StructData::PersistenceContainer(container) => {
+                            let mut actors = container.actors.iter_mut().collect::<Vec<_>>();
+
+                            actors.sort_by_key(|(unique_id, _)| **unique_id);
+
+                            for (_, actor) in actors {
+                                actor.archive.for_each_archive_mut(f)?;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SaveGameArchive {
+    pub fn save_version(&self) -> u32 {
+        self.header.save_game_file_version
+    }
+
+    pub fn version(&self) -> anyhow::Result<SaveVersion> {
+        SaveVersion::detect(&self.header)
+    }
+
+    pub fn build_number(&self) -> u32 {
+        self.header.build_number
+    }
+
+    pub fn set_build_number(&mut self, build_number: u32) {
+        self.header.build_number = build_number;
+    }
+
+    // properties kept as bytes by Reader::raw_fallback, they have to be written with the original name table
+    pub fn has_raw_properties(&self) -> bool {
+        let mut has_raw = false;
+
+        self.visit_properties(&mut |property| has_raw |= matches!(property.data, PropertyData::Raw(_)));
+
+        has_raw
+    }
+
+    pub fn root(&self) -> Option<&UObject> {
+        self.content.object_index.first()
+    }
+
+    pub fn root_mut(&mut self) -> Option<&mut UObject> {
+        self.content.object_index.first_mut()
+    }
+
+    // the first object of this archive (not nested ones) whose path contains `path_contains`
+    pub fn find_object(&self, path_contains: &str) -> Option<&UObject> {
+        self.content.object_index.iter().find(|object| object.object_path.contains(path_contains))
+    }
+
+    pub fn find_object_mut(&mut self, path_contains: &str) -> Option<&mut UObject> {
+        self.content.object_index.iter_mut().find(|object| object.object_path.contains(path_contains))
+    }
+
+    pub fn objects_matching<'a>(&'a self, pred: impl Fn(&UObject) -> bool + 'a) -> impl Iterator<Item = &'a UObject> {
+        self.content.object_index.iter().filter(move |object| pred(object))
+    }
+
+    // profile saves don't store a save timestamp, so this is None for them
+    pub fn last_saved(&self) -> Option<&DateTime> {
+        match &self.root()?.get(SAVE_DATE_TIME_PROPERTY)?.data {
+            PropertyData::Struct(property) => match &property.data {
+                StructData::DateTime(date_time) => Some(date_time),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn set_last_saved(&mut self, value: chrono::DateTime<chrono::Utc>) -> anyhow::Result<()> {
+        let date_time = self.root_mut()
+            .and_then(|root| root.get_mut(SAVE_DATE_TIME_PROPERTY))
+            .and_then(|property| match &mut property.data {
+                PropertyData::Struct(property) => match &mut property.data {
+                    StructData::DateTime(date_time) => Some(date_time),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .ok_or_else(|| anyhow::anyhow!("Save has no {} property", SAVE_DATE_TIME_PROPERTY))?;
+
+        *date_time = DateTime::from_chrono(value)?;
+
+        Ok(())
+    }
+
+    pub fn character_name(&self) -> anyhow::Result<&str> {
+        self.root()
+            .map(|root| root.get_string(CHARACTER_NAME_PROPERTY))
+            .transpose()?
+            .flatten()
+            .ok_or_else(|| anyhow::anyhow!("Save has no {} property", CHARACTER_NAME_PROPERTY))
+    }
+
+    pub fn set_character_name(&mut self, name: &str) -> anyhow::Result<()> {
+        self.root_mut()
+            .ok_or_else(|| anyhow::anyhow!("Save has no root object"))?
+            .set_string(CHARACTER_NAME_PROPERTY, name)
+    }
+
+    // compares two archives ignoring what the writer recomputes (name tables and property sizes)
+    pub fn semantic_eq(&self, other: &SaveGameArchive) -> anyhow::Result<bool> {
+        Ok(semantic_value(self)? == semantic_value(other)?)
+    }
+}
+
+// the JSON of a value without what the writer recomputes
+fn semantic_value<T: Serialize>(value: &T) -> anyhow::Result<serde_json::Value> {
+    fn strip(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.remove("name_table");
+                map.remove("size");
+                map.remove("__offset");
+                map.values_mut().for_each(strip);
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+
+    let mut value = serde_json::to_value(value)?;
+
+    strip(&mut value);
+
+    Ok(value)
+}
@@ -0,0 +1,228 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use serde_json::Value;
+use crate::properties::path::key_string;
+use crate::properties::{Property, PropertyData};
+use crate::sav::{semantic_value as to_value, ArchiveChange, SaveGameArchive, SaveGameArchiveContent, UObject};
+use crate::structs::{PersistenceContainer, StructData};
+
+impl SaveGameArchive {
+    // objects are matched by path, properties by name, arrays element-wise and
+    // map entries by key, like semantic_eq name tables and sizes are ignored
+    pub fn diff(&self, other: &SaveGameArchive) -> anyhow::Result<Vec<ArchiveChange>> {
+        let mut changes = Vec::new();
+
+        diff_value("header", to_value(&self.header)?, to_value(&other.header)?, &mut changes);
+        diff_content("", &self.content, &other.content, &mut changes)?;
+
+        Ok(changes)
+    }
+}
+
+impl fmt::Display for ArchiveChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveChange::Added { path, value } => write!(f, "+ {} = {}", path, value),
+            ArchiveChange::Removed { path, value } => write!(f, "- {} = {}", path, value),
+            ArchiveChange::Changed { path, old, new } => write!(f, "~ {}: {} -> {}", path, old, new),
+        }
+    }
+}
+
+fn diff_content(
+    prefix: &str,
+    content: &SaveGameArchiveContent,
+    other: &SaveGameArchiveContent,
+    changes: &mut Vec<ArchiveChange>,
+) -> anyhow::Result<()> {
+    let objects = objects_by_path(content);
+    let other_objects = objects_by_path(other);
+
+    for (path, object) in &objects {
+        let full_path = format!("{}{}", prefix, path);
+
+        match other_objects.iter().find(|(other_path, _)| other_path == path) {
+            Some((_, other_object)) => diff_object(&full_path, object, other_object, changes)?,
+            None => changes.push(ArchiveChange::Removed { path: full_path, value: to_value(object)? }),
+        }
+    }
+
+    for (path, object) in &other_objects {
+        if !objects.iter().any(|(existing, _)| existing == path) {
+            changes.push(ArchiveChange::Added { path: format!("{}{}", prefix, path), value: to_value(object)? });
+        }
+    }
+
+    Ok(())
+}
+
+// object paths aren't guaranteed to be unique, repeated ones get their occurrence appended
+fn objects_by_path(content: &SaveGameArchiveContent) -> Vec<(String, &UObject)> {
+    let mut seen = HashMap::new();
+
+    content.object_index.iter()
+        .map(|object| {
+            let count = seen.entry(object.object_path.as_str()).or_insert(0);
+            let path = match *count {
+                0 => object.object_path.clone(),
+                n => format!("{}#{}", object.object_path, n),
+            };
+
+            *count += 1;
+
+            (path, object)
+        })
+        .collect()
+}
+
+fn diff_object(path: &str, object: &UObject, other: &UObject, changes: &mut Vec<ArchiveChange>) -> anyhow::Result<()> {
+    diff_properties(&format!("{}:", path), &object.properties, &other.properties, changes)?;
+
+    diff_value(
+        &format!("{}:trailing_data", path),
+        to_value(&object.trailing_data)?,
+        to_value(&other.trailing_data)?,
+        changes,
+    );
+    diff_value(
+        &format!("{}:components", path),
+        to_value(&object.components)?,
+        to_value(&other.components)?,
+        changes,
+    );
+
+    Ok(())
+}
+
+// `prefix` is `<object>:` for top level properties and `<parent>.` for struct fields
+fn diff_properties(
+    prefix: &str,
+    properties: &[Property],
+    other: &[Property],
+    changes: &mut Vec<ArchiveChange>,
+) -> anyhow::Result<()> {
+    let name = |property: &Property| match property.index {
+        0 => format!("{}{}", prefix, property.name.value),
+        index => format!("{}{}#{}", prefix, property.name.value, index),
+    };
+
+    for property in properties {
+        let path = name(property);
+
+        match other.iter().find(|other| name(other) == path) {
+            Some(other) => diff_data(&path, &property.data, &other.data, changes)?,
+            None => changes.push(ArchiveChange::Removed { path, value: to_value(&property.data)? }),
+        }
+    }
+
+    for property in other {
+        let path = name(property);
+
+        if !properties.iter().any(|existing| name(existing) == path) {
+            changes.push(ArchiveChange::Added { path, value: to_value(&property.data)? });
+        }
+    }
+
+    Ok(())
+}
+
+fn diff_data(path: &str, data: &PropertyData, other: &PropertyData, changes: &mut Vec<ArchiveChange>) -> anyhow::Result<()> {
+    match (data, other) {
+        (PropertyData::Array(array), PropertyData::Array(other_array)) if array.inner_type.value == other_array.inner_type.value => {
+            for i in 0..array.elements.len().max(other_array.elements.len()) {
+                let path = format!("{}[{}]", path, i);
+
+                match (array.elements.get(i), other_array.elements.get(i)) {
+                    (Some(element), Some(other_element)) => diff_data(&path, element, other_element, changes)?,
+                    (Some(element), None) => changes.push(ArchiveChange::Removed { path, value: to_value(element)? }),
+                    (None, Some(element)) => changes.push(ArchiveChange::Added { path, value: to_value(element)? }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (PropertyData::Map(map), PropertyData::Map(other_map)) => {
+            let key = |key: &PropertyData| key_string(key).unwrap_or_else(|| format!("{:?}", key));
+
+            for (k, value) in &map.elements {
+                let k = key(k);
+                let path = format!("{}[{}]", path, k);
+
+                match other_map.elements.iter().find(|(other_k, _)| key(other_k) == k) {
+                    Some((_, other_value)) => diff_data(&path, value, other_value, changes)?,
+                    None => changes.push(ArchiveChange::Removed { path, value: to_value(value)? }),
+                }
+            }
+
+            for (k, value) in &other_map.elements {
+                let k = key(k);
+
+                if !map.elements.iter().any(|(existing, _)| key(existing) == k) {
+                    changes.push(ArchiveChange::Added { path: format!("{}[{}]", path, k), value: to_value(value)? });
+                }
+            }
+        }
+        (PropertyData::Struct(property), PropertyData::Struct(other_property)) => {
+            match (&property.data, &other_property.data) {
+                (StructData::Dynamic(dynamic_struct), StructData::Dynamic(other_struct)) => {
+                    diff_properties(&format!("{}.", path), &dynamic_struct.properties, &other_struct.properties, changes)?;
+                }
+                (StructData::PersistenceBlob(blob), StructData::PersistenceBlob(other_blob)) => {
+                    diff_content(&format!("{} > ", path), &blob.archive, &other_blob.archive, changes)?;
+                }
+                (StructData::PersistenceContainer(container), StructData::PersistenceContainer(other_container)) => {
+                    diff_container(path, container, other_container, changes)?;
+                }
+                _ => diff_value(path, to_value(data)?, to_value(other)?, changes),
+            }
+        }
+        _ => diff_value(path, to_value(data)?, to_value(other)?, changes),
+    }
+
+    Ok(())
+}
+
+fn diff_container(
+    path: &str,
+    container: &PersistenceContainer,
+    other: &PersistenceContainer,
+    changes: &mut Vec<ArchiveChange>,
+) -> anyhow::Result<()> {
+    diff_value(&format!("{}.destroyed", path), to_value(&container.destroyed)?, to_value(&other.destroyed)?, changes);
+
+    let unique_ids = container.actors.keys()
+        .chain(other.actors.keys())
+        .collect::<BTreeSet<_>>();
+
+    for unique_id in unique_ids {
+        let path = format!("{}[{}]", path, unique_id);
+
+        match (container.actors.get(unique_id), other.actors.get(unique_id)) {
+            (Some(actor), Some(other_actor)) => {
+                diff_value(
+                    &format!("{}.transform", path),
+                    to_value(&actor.transform)?,
+                    to_value(&other_actor.transform)?,
+                    changes,
+                );
+                diff_value(
+                    &format!("{}.dynamic_data", path),
+                    to_value(&actor.dynamic_data)?,
+                    to_value(&other_actor.dynamic_data)?,
+                    changes,
+                );
+                diff_content(&format!("{} > ", path), &actor.archive, &other_actor.archive, changes)?;
+            }
+            (Some(actor), None) => changes.push(ArchiveChange::Removed { path, value: to_value(actor)? }),
+            (None, Some(actor)) => changes.push(ArchiveChange::Added { path, value: to_value(actor)? }),
+            (None, None) => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn diff_value(path: &str, value: Value, other: Value, changes: &mut Vec<ArchiveChange>) {
+    if value != other {
+        changes.push(ArchiveChange::Changed { path: path.to_owned(), old: value, new: other });
+    }
+}
@@ -1,15 +1,18 @@
-use std::io::{Cursor, Read, Seek, SeekFrom};
-use anyhow::bail;
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use flate2::bufread::ZlibDecoder;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use anyhow::{bail, Context};
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use flate2::bufread::{GzDecoder, ZlibDecoder};
 use crate::components::{Component, ComponentType};
-use crate::io::{Reader, ReaderExt, Writer};
+use crate::io::{ArchiveReader, ArchiveWriter, Endian, Reader, ReaderExt, ObjectPadding};
 use crate::properties::Property;
-use crate::sav::{ARCHIVE_V2_HEADER_TAG, Compressor, FCompressedChunkInfo, NameTable, SavChunk, SaveGameArchive, SaveGameArchiveContent, SaveGameArchiveHeader, SavFile, UObject, UObjectLoadedData};
+use crate::sav::{ARCHIVE_V2_HEADER_TAG, ARCHIVE_V2_HEADER_TAG_SWAPPED, Compressor, FCompressedChunkInfo, MAX_CHUNK_SIZE, NameTable, ParseError, SavChunk, SaveGameArchive, SaveGameArchiveContent, SaveGameArchiveHeader, SaveVersion, SavFile, UObject, UObjectLoadedData};
 use crate::structs::{FName, FPackageVersion, FTopLevelAssetPath};
 
+// properties (None without a property list), trailing data, components and recorded offset of an object
+type ObjectData = (Option<Vec<Property>>, Vec<u8>, Option<Vec<Component>>, Option<u64>);
+
 impl Compressor {
-    fn read(reader: &mut Reader) -> anyhow::Result<Self> {
+    fn read<S: Read + Seek>(reader: &mut Reader<S>) -> anyhow::Result<Self> {
         let compressor = match reader.read_u8()? {
             0 => Compressor::Custom(reader.read_fstring()?),
             1 => Compressor::None,
@@ -26,20 +29,30 @@ impl Compressor {
 
 impl SavFile {
     pub fn get_content(&self) -> anyhow::Result<Vec<u8>> {
-        let mut uncompressed_data = Vec::with_capacity(self.content_size as usize);
+        self.decompress(true)
+    }
+
+    // for saves edited by tools that didn't update the checksum, a mismatch is only a warning
+    pub fn get_content_unchecked(&self) -> anyhow::Result<Vec<u8>> {
+        self.decompress(false)
+    }
+
+    fn decompress(&self, verify_crc: bool) -> anyhow::Result<Vec<u8>> {
+        // the chunk sizes are bounded when reading, the content size is not
+        let chunks_size = self.chunks.iter().map(|chunk| chunk.compression_info.uncompressed_size).sum::<u64>();
+        let mut uncompressed_data = Vec::with_capacity(chunks_size.min(self.content_size as u64) as usize + 8);
 
-        uncompressed_data.write_u32::<LittleEndian>(self.crc32)?;
-        uncompressed_data.write_u32::<LittleEndian>(self.content_size)?;
+        uncompressed_data.write_u32::<Endian>(self.crc32)?;
+        uncompressed_data.write_u32::<Endian>(self.content_size)?;
 
         for chunk in &self.chunks {
-            ZlibDecoder::new(chunk.compressed_data.as_slice())
-                .read_to_end(&mut uncompressed_data)?;
+            chunk.decompress(&mut uncompressed_data)?;
         }
 
         let mut cursor = Cursor::new(uncompressed_data);
 
         cursor.seek(SeekFrom::Start(8))?;
-        cursor.write_u32::<LittleEndian>(self.version)?;
+        cursor.write_u32::<Endian>(self.version)?;
 
         // crc32 check
 
@@ -49,28 +62,46 @@ impl SavFile {
 
         crc32.update(&cursor.get_ref()[4..]);
 
-        if crc32.finalize() != self.crc32 {
-            bail!("CRC32 mismatch");
+        let actual_crc32 = crc32.finalize();
+
+        if actual_crc32 != self.crc32 {
+            if verify_crc {
+                bail!("CRC32 mismatch (header has {:08X}, content has {:08X})", self.crc32, actual_crc32);
+            }
+
+            println!("[WARN] CRC32 mismatch (header has {:08X}, content has {:08X})", self.crc32, actual_crc32);
         }
 
         Ok(cursor.into_inner())
     }
 
+    // the decompressed archive as it is hashed, e.g. for diffing saves byte by byte
+    pub fn decompress_to<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_all(&self.get_content()?)?;
+
+        Ok(())
+    }
+
+    // the save version and game build without parsing the rest of the archive
+    pub fn read_header(&self) -> anyhow::Result<SaveGameArchiveHeader> {
+        SaveGameArchiveHeader::read(&mut Reader::new(self.get_content()?, ObjectPadding::Four))
+    }
+
     pub fn get_archive(&self) -> anyhow::Result<SaveGameArchive> {
         let content = self.get_content()?;
-        let mut reader = Reader::new(content, 4);
+        let mut reader = Reader::new(content, ObjectPadding::Four);
 
         SaveGameArchive::read(&mut reader)
     }
 }
 
 impl SavFile {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<Self> {
-        let size = reader.get_ref().len() as u64;
+    pub fn read<S: Read + Seek>(reader: &mut Reader<S>) -> anyhow::Result<Self> {
+        let size = reader.len();
 
-        let crc32 = reader.read_u32::<LittleEndian>()?;
-        let content_size = reader.read_u32::<LittleEndian>()?;
-        let version = reader.read_u32::<LittleEndian>()?;
+        let crc32 = reader.read_u32::<Endian>()?;
+        let content_size = reader.read_u32::<Endian>()?;
+        let version = reader.read_u32::<Endian>()?;
 
         let mut chunks = Vec::new();
 
@@ -90,9 +121,9 @@ impl SavFile {
 }
 
 impl FCompressedChunkInfo {
-    fn read(reader: &mut Reader) -> anyhow::Result<Self> {
-        let compressed_size = reader.read_u64::<LittleEndian>()?;
-        let uncompressed_size = reader.read_u64::<LittleEndian>()?;
+    fn read<S: Read + Seek>(reader: &mut Reader<S>) -> anyhow::Result<Self> {
+        let compressed_size = reader.read_u64::<Endian>()?;
+        let uncompressed_size = reader.read_u64::<Endian>()?;
 
         let compression_info = FCompressedChunkInfo {
             compressed_size,
@@ -104,19 +135,39 @@ impl FCompressedChunkInfo {
 }
 
 impl SavChunk {
-    fn read(reader: &mut Reader) -> anyhow::Result<Self> {
-        let package_file_tag = reader.read_u64::<LittleEndian>()?;
-
-        if package_file_tag != ARCHIVE_V2_HEADER_TAG {
-            bail!("Unsupported package file tag: {}", package_file_tag);
+    fn read<S: Read + Seek>(reader: &mut Reader<S>) -> anyhow::Result<Self> {
+        let package_file_tag = reader.read_u64::<Endian>()?;
+
+        // only the tag of PC saves is known, anything else is most likely
+        // an encrypted or byte-swapped save
+        match package_file_tag {
+            ARCHIVE_V2_HEADER_TAG => {}
+            ARCHIVE_V2_HEADER_TAG_SWAPPED => bail!(
+                "Unsupported package file tag: {:016X} (byte-swapped save from a big-endian platform)",
+                package_file_tag,
+            ),
+            _ => bail!(
+                "Unsupported package file tag: {:016X} (expected {:016X}, the save may be encrypted or from an unsupported platform)",
+                package_file_tag,
+                ARCHIVE_V2_HEADER_TAG,
+            ),
         }
 
-        let _uncompressed_size = reader.read_u64::<LittleEndian>()?; // can be ignored
+        let _uncompressed_size = reader.read_u64::<Endian>()?; // can be ignored
         let compressor = Compressor::read(reader)?;
         let compression_info = FCompressedChunkInfo::read(reader)?;
         let _compression_info_2 = FCompressedChunkInfo::read(reader)?; // can be ignored
 
-        let mut data = vec![0u8; compression_info.compressed_size as usize];
+        if compression_info.uncompressed_size > MAX_CHUNK_SIZE {
+            bail!(
+                "Chunk at offset {:#X} is too large ({} bytes uncompressed, at most {} are supported)",
+                reader.position(),
+                compression_info.uncompressed_size,
+                MAX_CHUNK_SIZE,
+            );
+        }
+
+        let mut data = vec![0u8; reader.check_length(compression_info.compressed_size)?];
 
         reader.read_exact(&mut data)?;
 
@@ -131,34 +182,95 @@ impl SavChunk {
     }
 }
 
+impl SavChunk {
+    pub(crate) fn decompress(&self, output: &mut Vec<u8>) -> anyhow::Result<()> {
+        let data = self.compressed_data.as_slice();
+
+        match &self.compressor {
+            Compressor::None => output.extend_from_slice(data),
+            Compressor::Zlib => {
+                ZlibDecoder::new(data).read_to_end(output)?;
+            }
+            Compressor::Oodle => self.decompress_oodle(output)?,
+            Compressor::Gzip => {
+                GzDecoder::new(data).read_to_end(output)?;
+            }
+            Compressor::LZ4 => {
+                let size = self.compression_info.uncompressed_size as usize;
+
+                output.extend(lz4_flex::block::decompress(data, size)?);
+            }
+            Compressor::Custom(name) => bail!("Unsupported custom compressor: {}", name),
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "oodle")]
+    fn decompress_oodle(&self, output: &mut Vec<u8>) -> anyhow::Result<()> {
+        // the output is allocated up front, so chunks that weren't read by SavChunk::read are checked again
+        if self.compression_info.uncompressed_size > MAX_CHUNK_SIZE {
+            bail!(
+                "Oodle chunk is too large ({} bytes uncompressed, at most {} are supported)",
+                self.compression_info.uncompressed_size,
+                MAX_CHUNK_SIZE,
+            );
+        }
+
+        let start = output.len();
+
+        output.resize(start + self.compression_info.uncompressed_size as usize, 0);
+
+        let size = oozextract::Extractor::new()
+            .read_from_slice(&self.compressed_data, &mut output[start..])
+            .map_err(|e| anyhow::anyhow!("Oodle decompression failed: {}", e))?;
+
+        output.truncate(start + size);
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "oodle"))]
+    fn decompress_oodle(&self, _output: &mut Vec<u8>) -> anyhow::Result<()> {
+        bail!("Oodle compressed chunks need the `oodle` feature")
+    }
+}
+
 impl SaveGameArchiveHeader {
-    fn read(reader: &mut Reader) -> anyhow::Result<Self> {
-        let _crc32 = reader.read_u32::<LittleEndian>()?;
-        let _size = reader.read_u32::<LittleEndian>()?;
-        let save_game_file_version = reader.read_u32::<LittleEndian>()?;
-        let build_number = reader.read_u32::<LittleEndian>()?;
+    fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let _crc32 = reader.read_u32::<Endian>()?;
+        let _size = reader.read_u32::<Endian>()?;
+        let save_game_file_version = reader.read_u32::<Endian>()?;
+        let build_number = reader.read_u32::<Endian>()?;
 
-        Ok(SaveGameArchiveHeader {
+        let header = SaveGameArchiveHeader {
             save_game_file_version,
             build_number,
-        })
+        };
+
+        reader.set_save_version(SaveVersion::detect(&header)?);
+
+        Ok(header)
     }
 }
 
 impl NameTable {
-    pub fn read_name(&self, reader: &mut Reader) -> anyhow::Result<FName> {
+    pub fn read_name<R: ArchiveReader>(&self, reader: &mut R) -> anyhow::Result<FName> {
         const HAS_NUMBER: u16 = 1 << 15;
 
-        let mut index = reader.read_u16::<LittleEndian>()?;
+        let mut index = reader.read_u16::<Endian>()?;
 
         let number = if index & HAS_NUMBER != 0 {
             index &= !HAS_NUMBER;
-            Some(reader.read_u32::<LittleEndian>()?)
+            Some(reader.read_u32::<Endian>()?)
         } else {
             None
         };
 
-        let name = self.list.get(index as usize).unwrap().clone();
+        let name = match self.list.get(index as usize) {
+            Some(name) => name.clone(),
+            None => bail!("Name index {} is out of range (name table has {} entries)", index, self.list.len()),
+        };
 
         Ok(FName {
             value: name,
@@ -168,10 +280,34 @@ impl NameTable {
 }
 
 impl SaveGameArchiveContent {
-    pub fn read(
-        reader: &mut Reader,
+    pub fn read<R: ArchiveReader>(
+        reader: &mut R,
         has_ue_version: bool,
         has_top_level_asset_path: bool,
+    ) -> anyhow::Result<Self> {
+        Self::read_with(reader, has_ue_version, has_top_level_asset_path, None)
+    }
+
+    // objects whose data fails to parse are skipped and reported instead of failing the whole
+    // archive, they are left without properties and the archive is marked as partial
+    pub fn read_lenient<R: ArchiveReader>(
+        reader: &mut R,
+        has_ue_version: bool,
+        has_top_level_asset_path: bool,
+    ) -> anyhow::Result<(Self, Vec<ParseError>)> {
+        let mut errors = Vec::new();
+        let mut content = Self::read_with(reader, has_ue_version, has_top_level_asset_path, Some(&mut errors))?;
+
+        content.partial |= !errors.is_empty();
+
+        Ok((content, errors))
+    }
+
+    fn read_with<R: ArchiveReader>(
+        reader: &mut R,
+        has_ue_version: bool,
+        has_top_level_asset_path: bool,
+        mut errors: Option<&mut Vec<ParseError>>,
     ) -> anyhow::Result<Self> {
         let package_version = if has_ue_version {
             Some(FPackageVersion::read(reader)?)
@@ -185,13 +321,13 @@ impl SaveGameArchiveContent {
             None
         };
 
-        let name_table_offset = reader.read_u64::<LittleEndian>()?;
+        let name_table_offset = reader.read_u64::<Endian>()?;
         let start_pos = reader.position();
 
-        reader.seek(SeekFrom::Start(name_table_offset))?;
+        reader.seek_checked(name_table_offset).context("Invalid name table offset")?;
 
-        let name_table_size = reader.read_u32::<LittleEndian>()?;
-        let mut name_table = Vec::with_capacity(name_table_size as usize);
+        let name_table_size = reader.read_u32::<Endian>()?;
+        let mut name_table = Vec::with_capacity(reader.capacity_for(name_table_size));
 
         for _ in 0..name_table_size {
             name_table.push(reader.read_fstring()?);
@@ -199,65 +335,183 @@ impl SaveGameArchiveContent {
 
         reader.seek(SeekFrom::Start(start_pos))?;
 
-        let version = reader.read_u32::<LittleEndian>()?;
-        let object_index_offset = reader.read_u64::<LittleEndian>()?;
+        let version = reader.read_u32::<Endian>()?;
+        let object_index_offset = reader.read_u64::<Endian>()?;
 
         let start_pos = reader.position();
 
-        reader.seek(SeekFrom::Start(object_index_offset))?;
+        reader.seek_checked(object_index_offset).context("Invalid object index offset")?;
 
-        let object_count = reader.read_u32::<LittleEndian>()?;
-        let object_index = Vec::with_capacity(object_count as usize);
+        let object_count = reader.read_u32::<Endian>()?;
+        let object_index = Vec::with_capacity(reader.capacity_for(object_count));
 
         let mut sav_data = SaveGameArchiveContent {
             package_version,
             save_game_class_path,
-            name_table: NameTable { list: name_table },
+            name_table: NameTable::new(name_table),
             object_index,
             version,
+            partial: false,
         };
 
         for i in 0..object_count {
-            let object = UObject::read(reader, &sav_data, i)?;
-
-            sav_data.object_index.push(object);
+            match UObject::read(reader, &sav_data, i) {
+                Ok(object) => sav_data.object_index.push(object),
+                Err(err) if reader.partial_recovery() => {
+                    println!("[WARN] Object index is truncated at object {}: {:#}", i, err);
+
+                    sav_data.partial = true;
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
         }
 
         reader.seek(SeekFrom::Start(start_pos))?;
 
+        // recovering a truncated save needs to know how far parsing got, so it stays sequential
+        #[cfg(feature = "parallel")]
+        if !reader.partial_recovery() {
+            sav_data.read_object_data_parallel(reader, object_count, errors)?;
+
+            return Ok(sav_data);
+        }
+
+        for i in 0..object_count {
+            match sav_data.read_object_data(reader, errors.as_deref_mut()) {
+                Ok(()) => {}
+                Err(err) if reader.partial_recovery() => {
+                    println!("[WARN] Object data is truncated after {} objects: {:#}", i, err);
+
+                    sav_data.partial = true;
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(sav_data)
+    }
+
+    fn read_object_data<R: ArchiveReader>(
+        &mut self,
+        reader: &mut R,
+        errors: Option<&mut Vec<ParseError>>,
+    ) -> anyhow::Result<()> {
+        let object_id = reader.read_u32::<Endian>()?;
+        let offset = reader.position();
+
+        match (self.parse_object_data(reader, object_id, offset), errors) {
+            (Ok(data), _) => self.set_object_data(object_id, data),
+            (Err(err), Some(errors)) => {
+                reader.seek(SeekFrom::Start(offset))?;
+                skip_object_data(reader)?;
+
+                errors.push(ParseError::new(object_id, offset, &err));
+            }
+            (Err(err), None) => return Err(err),
+        }
+
+        Ok(())
+    }
+
+    fn set_object_data(&mut self, object_id: u32, (properties, trailing_data, components, offset): ObjectData) {
+        let object = &mut self.object_index[object_id as usize];
+
+        object.empty_property_list = properties.as_ref().is_some_and(Vec::is_empty);
+        object.properties = properties.unwrap_or_default();
+        object.trailing_data = trailing_data;
+        object.components = components;
+        object.offset = offset;
+    }
+
+    // the data of each object is laid out back to back, so the regions are
+    // found sequentially and only their contents are parsed in parallel
+    #[cfg(feature = "parallel")]
+    fn read_object_data_parallel<R: ArchiveReader>(
+        &mut self,
+        reader: &mut R,
+        object_count: u32,
+        mut errors: Option<&mut Vec<ParseError>>,
+    ) -> anyhow::Result<()> {
+        use rayon::prelude::*;
+
+        let mut regions = Vec::with_capacity(reader.capacity_for(object_count));
+
         for _ in 0..object_count {
-            let object_id = reader.read_u32::<LittleEndian>()?;
-            let object = &sav_data.object_index[object_id as usize];
+            let object_id = reader.read_u32::<Endian>()?;
+            let start_pos = reader.position();
+
+            skip_object_data(reader)?;
+
+            let length = reader.position() - start_pos;
 
-            let properties = object.read_data(reader, &sav_data, object_id)?;
+            reader.seek(SeekFrom::Start(start_pos))?;
+
+            let mut bytes = vec![0; reader.check_length(length)?];
+            reader.read_exact(&mut bytes)?;
+
+            regions.push((object_id, start_pos, reader.sub_reader(bytes, reader.object_padding())));
+        }
+
+        let objects = regions.into_par_iter()
+            .map(|(object_id, offset, mut sub_reader)| {
+                (object_id, offset, self.parse_object_data(&mut sub_reader, object_id, offset))
+            })
+            .collect::<Vec<_>>();
+
+        for (object_id, offset, data) in objects {
+            match (data, errors.as_deref_mut()) {
+                (Ok(data), _) => self.set_object_data(object_id, data),
+                (Err(err), Some(errors)) => errors.push(ParseError::new(object_id, offset, &err)),
+                (Err(err), None) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    // `offset` is where the object's data starts in the archive, it's only used for errors since
+    // the parallel reader hands each object its own sub reader (offsets within it are relative)
+    fn parse_object_data<R: ArchiveReader>(
+        &self,
+        reader: &mut R,
+        object_id: u32,
+        offset: u64,
+    ) -> anyhow::Result<ObjectData> {
+        let object = self.object_index.get(object_id as usize)
+            .ok_or_else(|| anyhow::anyhow!("Object id {} is out of range", object_id))?;
+
+        let parse = |reader: &mut R| -> anyhow::Result<ObjectData> {
+            let offset = reader.record_offsets().then(|| reader.absolute_position());
+            let (properties, trailing_data) = object.read_data(reader, self, object_id)?;
 
             let is_actor = reader.read_u8()? != 0;
             let components = if is_actor {
-                Some(object.read_components(reader, &sav_data)?)
+                Some(object.read_components(reader, self)?)
             } else {
                 None
             };
 
-            let object = &mut sav_data.object_index[object_id as usize];
-
-            object.properties = properties;
-            object.components = components;
-        }
+            Ok((properties, trailing_data, components, offset))
+        };
 
-        Ok(sav_data)
+        parse(reader).with_context(|| {
+            format!("while parsing object {} ({}) at offset {:#X}", object_id, object.object_path, offset)
+        })
     }
 
-    pub fn read_name(
+    pub fn read_name<R: ArchiveReader>(
         &self,
-        reader: &mut Reader,
+        reader: &mut R,
     ) -> anyhow::Result<FName> {
         self.name_table.read_name(reader)
     }
 }
 
 impl UObject {
-    pub fn read(
-        reader: &mut Reader,
+    pub fn read<R: ArchiveReader>(
+        reader: &mut R,
         sav_data: &SaveGameArchiveContent,
         object_id: u32,
     ) -> anyhow::Result<UObject> {
@@ -272,9 +526,9 @@ impl UObject {
             reader.read_fstring()?
         };
 
-        let loaded_data = if !was_loaded {
+        let loaded_data = if reader.save_version().has_loaded_data(was_loaded) {
             let object_name = sav_data.read_name(reader)?;
-            let outer_id = reader.read_u32::<LittleEndian>()?;
+            let outer_id = reader.read_u32::<Endian>()?;
 
             Some(UObjectLoadedData {
                 name: object_name,
@@ -290,64 +544,89 @@ impl UObject {
             object_path,
             loaded_data,
             properties: Vec::new(),
+            empty_property_list: false,
+            trailing_data: Vec::new(),
             components: None,
+            offset: None,
         })
     }
 
-    pub fn read_data(
+    pub fn read_data<R: ArchiveReader>(
         &self,
-        reader: &mut Reader,
+        reader: &mut R,
         sav_data: &SaveGameArchiveContent,
         id: u32,
-    ) -> anyhow::Result<Vec<Property>> {
-        let object_length = reader.read_u32::<LittleEndian>()?;
+    ) -> anyhow::Result<(Option<Vec<Property>>, Vec<u8>)> {
+        let object_length = reader.read_u32::<Endian>()?;
 
         let start_pos = reader.position();
         let properties = if object_length > 0 {
             let properties = Property::read_multiple(reader, sav_data)?;
 
-            if reader.object_padding == 8 && id == 0 {
-                assert_eq!(reader.read_u64::<LittleEndian>()?, 0);
-            } else {
-                assert_eq!(reader.read_u32::<LittleEndian>()?, 0);
+            // see io::ObjectPadding, the padding depends on the nesting, its size on the save version
+            let padding_pos = reader.position();
+            let padding = reader.read_uint::<Endian>(reader.save_version().terminator_size(reader.object_padding(), id))?;
+
+            if padding != 0 {
+                bail!(
+                    "Object {} has non-zero padding {:#X} after its properties at offset {:#X}",
+                    id,
+                    padding,
+                    padding_pos,
+                );
             }
 
-            properties
+            Some(properties)
         } else {
-            Vec::new()
+            None
         };
 
-        if reader.position() - start_pos != object_length as u64 {
-            // TODO: There are some bytes that are not read, but I don't know what they are yet
+        let end_pos = start_pos + object_length as u64;
+        let mut trailing_data = Vec::new();
 
+        if reader.position() < end_pos {
+            // TODO: There are some bytes that are not read, but I don't know what they are yet
+            trailing_data = vec![0; reader.check_length(end_pos - reader.position())?];
+            reader.read_exact(&mut trailing_data)?;
+
+            // zeroed bytes are plain padding, anything else is worth a closer look
+            if trailing_data.iter().any(|&b| b != 0) {
+                println!(
+                    "[WARN] Object {} has {} unknown trailing bytes",
+                    self.object_id,
+                    trailing_data.len(),
+                );
+            }
+        } else if reader.position() > end_pos {
             println!(
-                "[WARN] Object {} has {} bytes, but only {} bytes were read",
+                "[WARN] Object {} has {} bytes, but {} bytes were read",
                 self.object_id,
                 object_length,
                 reader.position() - start_pos,
             );
 
-            reader.seek(SeekFrom::Start(start_pos + object_length as u64))?;
+            reader.seek(SeekFrom::Start(end_pos))?;
         }
 
-        Ok(properties)
+        Ok((properties, trailing_data))
     }
 
-    pub fn read_components(
+    pub fn read_components<R: ArchiveReader>(
         &self,
-        reader: &mut Reader,
+        reader: &mut R,
         sav_data: &SaveGameArchiveContent,
     ) -> anyhow::Result<Vec<Component>> {
-        let component_count = reader.read_u32::<LittleEndian>()?;
-        let mut components = Vec::with_capacity(component_count as usize);
+        let component_count = reader.read_u32::<Endian>()?;
+        let mut components = Vec::with_capacity(reader.capacity_for(component_count));
 
         for _ in 0..component_count {
             let component_key = reader.read_fstring()?;
-            let object_length = reader.read_u32::<LittleEndian>()?;
+            let object_length = reader.read_u32::<Endian>()?;
 
             let start_pos = reader.position();
 
-            let component = ComponentType::read(reader, sav_data, &component_key)?;
+            let component = ComponentType::read(reader, sav_data, &component_key)
+                .with_context(|| format!("while parsing component {} at offset {:#X}", component_key, start_pos))?;
 
             if reader.position() - start_pos != object_length as u64 {
                 bail!(
@@ -369,17 +648,58 @@ impl UObject {
 }
 
 impl SaveGameArchive {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<Self> {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
         let header = SaveGameArchiveHeader::read(reader)?;
         let content = SaveGameArchiveContent::read(reader, true, true)?;
 
         Ok(SaveGameArchive { header, content })
     }
 
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
+    pub fn read_lenient<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<(Self, Vec<ParseError>)> {
+        let header = SaveGameArchiveHeader::read(reader)?;
+        let (content, errors) = SaveGameArchiveContent::read_lenient(reader, true, true)?;
+
+        Ok((SaveGameArchive { header, content }, errors))
+    }
+
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
         self.header.write(writer)?;
         self.content.write(writer)?;
 
         Ok(())
     }
 }
+
+impl ParseError {
+    fn new(object_id: u32, offset: u64, err: &anyhow::Error) -> ParseError {
+        ParseError {
+            object_id,
+            offset,
+            message: format!("{:#}", err),
+        }
+    }
+}
+
+// utility functions
+
+// skips the length prefixed data and components of an object, which
+// is possible without understanding any of the properties
+fn skip_object_data<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<()> {
+    let object_length = reader.read_u32::<Endian>()?;
+    reader.seek(SeekFrom::Current(object_length as i64))?;
+
+    let is_actor = reader.read_u8()? != 0;
+
+    if is_actor {
+        let component_count = reader.read_u32::<Endian>()?;
+
+        for _ in 0..component_count {
+            reader.read_fstring()?;
+
+            let component_length = reader.read_u32::<Endian>()?;
+            reader.seek(SeekFrom::Current(component_length as i64))?;
+        }
+    }
+
+    Ok(())
+}
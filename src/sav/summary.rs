@@ -0,0 +1,56 @@
+use std::fmt;
+use crate::components::ComponentType;
+use crate::sav::{SaveGameArchive, SaveSummary};
+
+impl SaveGameArchive {
+    pub fn summary(&self) -> SaveSummary {
+        let mut summary = SaveSummary {
+            names: self.content.name_table.list.len(),
+            actors: self.content.actors().len(),
+            ..SaveSummary::default()
+        };
+
+        for archive in self.content.archives() {
+            summary.archives += 1;
+            summary.objects += archive.object_index.len();
+
+            for component in archive.object_index.iter().flat_map(|object| object.components.iter().flatten()) {
+                *summary.components.entry(component_type_name(&component.component_type).to_owned()).or_default() += 1;
+            }
+        }
+
+        self.visit_properties(&mut |_| summary.properties += 1);
+
+        summary
+    }
+}
+
+impl fmt::Display for SaveSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "archives: {}", self.archives)?;
+        writeln!(f, "objects: {}", self.objects)?;
+        writeln!(f, "actors: {}", self.actors)?;
+        writeln!(f, "names: {}", self.names)?;
+        writeln!(f, "properties: {}", self.properties)?;
+        write!(f, "components: {}", self.components.values().sum::<usize>())?;
+
+        for (component_type, count) in &self.components {
+            write!(f, "\n  {}: {}", component_type, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+// named after the component keys they're read from, any other key is a dynamic struct
+fn component_type_name(component_type: &ComponentType) -> &'static str {
+    match component_type {
+        ComponentType::GlobalVariables(_) => "GlobalVariables",
+        ComponentType::Variables(_) => "Variables",
+        ComponentType::Variable(_) => "Variable",
+        ComponentType::PersistenceKeys(_) => "PersistenceKeys",
+        ComponentType::PersistanceKeys1(_) => "PersistanceKeys1",
+        ComponentType::PersistenceKeys1(_) => "PersistenceKeys1",
+        ComponentType::DynamicStruct(_) => "DynamicStruct",
+    }
+}
@@ -0,0 +1,291 @@
+use std::io::Write;
+use crate::components::{Component, ComponentType, VariableValue};
+use crate::properties::{BytePropertyValue, Property, PropertyData, TextPropertyData};
+use crate::sav::{SaveGameArchive, SaveGameArchiveContent, UObject};
+use crate::structs::StructData;
+
+// arrays and maps with more elements than this only print their length
+const MAX_ELEMENTS: usize = 16;
+
+struct TreePrinter<'a, W: Write> {
+    writer: &'a mut W,
+    max_depth: usize,
+}
+
+impl SaveGameArchive {
+    // an indented overview of all objects, components and properties,
+    // anything nested deeper than `max_depth` is elided
+    pub fn dump_tree(&self, writer: &mut impl Write, max_depth: usize) -> anyhow::Result<()> {
+        writeln!(
+            writer,
+            "SaveGameArchive (version {}, build {})",
+            self.header.save_game_file_version,
+            self.header.build_number,
+        )?;
+
+        TreePrinter { writer, max_depth }.content(&self.content, 1)
+    }
+}
+
+impl<W: Write> TreePrinter<'_, W> {
+    fn line(&mut self, depth: usize, text: &str) -> anyhow::Result<()> {
+        writeln!(self.writer, "{:indent$}{}", "", text, indent = depth * 2)?;
+
+        Ok(())
+    }
+
+    // prints `...` instead of children that are too deep
+    fn descend(&mut self, depth: usize) -> anyhow::Result<bool> {
+        if depth > self.max_depth {
+            self.line(depth, "...")?;
+
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    fn content(&mut self, content: &SaveGameArchiveContent, depth: usize) -> anyhow::Result<()> {
+        if content.object_index.is_empty() || !self.descend(depth)? {
+            return Ok(());
+        }
+
+        for object in &content.object_index {
+            self.object(object, depth)?;
+        }
+
+        Ok(())
+    }
+
+    fn object(&mut self, object: &UObject, depth: usize) -> anyhow::Result<()> {
+        match &object.loaded_data {
+            Some(loaded_data) => self.line(depth, &format!(
+                "[{}] {} ({}, outer {})",
+                object.object_id,
+                object.object_path,
+                loaded_data.name.value,
+                loaded_data.outer_id,
+            ))?,
+            None => self.line(depth, &format!("[{}] {}", object.object_id, object.object_path))?,
+        }
+
+        let has_children = !object.properties.is_empty()
+            || !object.trailing_data.is_empty()
+            || object.components.is_some();
+
+        if !has_children || !self.descend(depth + 1)? {
+            return Ok(());
+        }
+
+        self.properties(&object.properties, depth + 1)?;
+
+        if !object.trailing_data.is_empty() {
+            self.line(depth + 1, &format!("trailing data: [{} bytes]", object.trailing_data.len()))?;
+        }
+
+        if let Some(components) = &object.components {
+            self.line(depth + 1, &format!("components: [{} components]", components.len()))?;
+
+            for component in components {
+                self.component(component, depth + 2)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn component(&mut self, component: &Component, depth: usize) -> anyhow::Result<()> {
+        match &component.component_type {
+            ComponentType::DynamicStruct(dynamic_struct) => {
+                self.line(depth, &format!("{}: DynamicStruct", component.component_key))?;
+                self.nested_properties(&dynamic_struct.properties, depth + 1)?;
+            }
+            ComponentType::GlobalVariables(variables)
+            | ComponentType::Variables(variables)
+            | ComponentType::Variable(variables)
+            | ComponentType::PersistenceKeys(variables)
+            | ComponentType::PersistanceKeys1(variables)
+            | ComponentType::PersistenceKeys1(variables) => {
+                self.line(depth, &format!("{}: {}", component.component_key, variables.name.value))?;
+
+                if variables.variables.is_empty() || !self.descend(depth + 1)? {
+                    return Ok(());
+                }
+
+                for variable in &variables.variables {
+                    let value = match &variable.value {
+                        VariableValue::None => "None".to_owned(),
+                        VariableValue::Bool(value) => value.to_string(),
+                        VariableValue::Int(value) => value.to_string(),
+                        VariableValue::Float(value) => value.to_string(),
+                        VariableValue::Name(value) => value.value.clone(),
+                    };
+
+                    self.line(depth + 1, &format!("{}: {}", variable.name.value, value))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn properties(&mut self, properties: &[Property], depth: usize) -> anyhow::Result<()> {
+        for property in properties {
+            self.line(depth, &format!("{}: {}", property.name.value, summary(&property.data)))?;
+            self.children(&property.data, depth + 1)?;
+        }
+
+        Ok(())
+    }
+
+    fn nested_properties(&mut self, properties: &[Property], depth: usize) -> anyhow::Result<()> {
+        if !properties.is_empty() && self.descend(depth)? {
+            self.properties(properties, depth)?;
+        }
+
+        Ok(())
+    }
+
+    fn children(&mut self, data: &PropertyData, depth: usize) -> anyhow::Result<()> {
+        match data {
+            PropertyData::Array(array) => {
+                if array.elements.is_empty() || array.elements.len() > MAX_ELEMENTS || !self.descend(depth)? {
+                    return Ok(());
+                }
+
+                for (i, element) in array.elements.iter().enumerate() {
+                    self.line(depth, &format!("[{}]: {}", i, summary(element)))?;
+                    self.children(element, depth + 1)?;
+                }
+            }
+            PropertyData::Map(map) => {
+                if map.elements.is_empty() || map.elements.len() > MAX_ELEMENTS || !self.descend(depth)? {
+                    return Ok(());
+                }
+
+                for (key, value) in &map.elements {
+                    self.line(depth, &format!("[{}]: {}", summary(key), summary(value)))?;
+                    self.children(value, depth + 1)?;
+                }
+            }
+            PropertyData::Struct(property) => match &property.data {
+                StructData::Dynamic(dynamic_struct) => self.nested_properties(&dynamic_struct.properties, depth)?,
+                StructData::PersistenceBlob(blob) => self.content(&blob.archive, depth)?,
+                StructData::PersistenceContainer(container) => {
+                    if container.actors.is_empty() || !self.descend(depth)? {
+                        return Ok(());
+                    }
+
+                    let mut actors = container.actors.iter().collect::<Vec<_>>();
+
+                    actors.sort_by_key(|(unique_id, _)| **unique_id);
+
+                    for (unique_id, actor) in actors {
+                        match &actor.dynamic_data {
+                            Some(dynamic_data) => self.line(
+                                depth,
+                                &format!("actor {} ({})", unique_id, dynamic_data.class_path.path),
+                            )?,
+                            None => self.line(depth, &format!("actor {}", unique_id))?,
+                        }
+
+                        self.content(&actor.archive, depth + 1)?;
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+// a one line description of a value, nested values are printed as children
+fn summary(data: &PropertyData) -> String {
+    match data {
+        PropertyData::Byte(property) => match &property.value {
+            BytePropertyValue::Enum(value) => value.value.clone(),
+            BytePropertyValue::Byte(value) => value.to_string(),
+        },
+        PropertyData::Bool(value) => value.to_string(),
+        PropertyData::Enum(property) => property.value.value.clone(),
+        PropertyData::Int16(value) => value.to_string(),
+        PropertyData::Int32(value) => value.to_string(),
+        PropertyData::Int64(value) => value.to_string(),
+        PropertyData::UInt16(value) => value.to_string(),
+        PropertyData::UInt32(value) => value.to_string(),
+        PropertyData::UInt64(value) => value.to_string(),
+        PropertyData::Float(value) => value.to_string(),
+        PropertyData::Double(value) => value.to_string(),
+        PropertyData::Map(map) => format!(
+            "Map<{}, {}> [{} entries]",
+            map.key_type.value,
+            map.value_type.value,
+            map.elements.len(),
+        ),
+        PropertyData::Array(array) => format!("Array<{}> [{} elements]", array.inner_type.value, array.elements.len()),
+        PropertyData::Object(index) => format!("Object({})", index),
+        PropertyData::SoftObject(path) => format!("{:?}", path),
+        PropertyData::Name(name) => name.to_display_string(),
+        PropertyData::Struct(property) => match &property.data {
+            StructData::SoftClassPath(path) | StructData::SoftObjectPath(path) => format!("{:?}", path),
+            StructData::PersistenceBlob(_) => "PersistenceBlob".to_owned(),
+            StructData::PersistenceContainer(container) => format!(
+                "PersistenceContainer [{} actors, {} destroyed]",
+                container.actors.len(),
+                container.destroyed.len(),
+            ),
+            StructData::Guid(guid) => guid.to_string(),
+            StructData::Timespan(timespan) => format!("Timespan({})", timespan),
+            StructData::DateTime(date_time) => match date_time.to_chrono() {
+                Some(date_time) => date_time.to_rfc3339(),
+                None => format!("DateTime({})", date_time.value),
+            },
+            StructData::Vector(vector) => format!("({}, {}, {})", vector.x, vector.y, vector.z),
+            StructData::Rotator(rotator) => format!(
+                "(pitch {}, yaw {}, roll {})",
+                rotator.pitch,
+                rotator.yaw,
+                rotator.roll,
+            ),
+            StructData::Quat(quat) => format!("({}, {}, {}, {})", quat.w, quat.x, quat.y, quat.z),
+            StructData::IntVector(vector) => format!("({}, {}, {})", vector.x, vector.y, vector.z),
+            StructData::LinearColor(color) => format!("rgba({}, {}, {}, {})", color.r, color.g, color.b, color.a),
+            StructData::Box(bounds) => format!(
+                "({}, {}, {}) - ({}, {}, {})",
+                bounds.min.x,
+                bounds.min.y,
+                bounds.min.z,
+                bounds.max.x,
+                bounds.max.y,
+                bounds.max.z,
+            ),
+            StructData::Box2D(bounds) => format!("({}, {}) - ({}, {})", bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y),
+            StructData::Sphere(sphere) => format!(
+                "({}, {}, {}) r {}",
+                sphere.center.x,
+                sphere.center.y,
+                sphere.center.z,
+                sphere.w,
+            ),
+            StructData::Dynamic(_) => property.struct_name.value.clone(),
+            StructData::RawBlob(bytes) => format!("{} [{} bytes]", property.struct_name.value, bytes.len()),
+        },
+        PropertyData::Str(value) => format!("{:?}", value),
+        PropertyData::StructReference(guid) => guid.to_string(),
+        PropertyData::Text(text) => match &text.data {
+            TextPropertyData::Base { source_string, .. } => format!("{:?}", source_string),
+            TextPropertyData::None { culture_invariant_string } => match culture_invariant_string {
+                Some(value) => format!("{:?}", value),
+                None => "None".to_owned(),
+            },
+            TextPropertyData::ArgumentFormat { arguments, .. } => {
+                format!("ArgumentFormat [{} arguments]", arguments.len())
+            }
+            TextPropertyData::AsNumber { .. } => "AsNumber".to_owned(),
+            TextPropertyData::AsDate { .. } => "AsDate".to_owned(),
+        },
+        PropertyData::Raw(bytes) => format!("Raw [{} bytes]", bytes.len()),
+    }
+}
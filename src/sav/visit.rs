@@ -0,0 +1,117 @@
+use crate::components::ComponentType;
+use crate::properties::{Property, PropertyData};
+use crate::sav::{SaveGameArchive, SaveGameArchiveContent};
+use crate::structs::StructData;
+
+// traversal is depth-first and pre-order: objects in object index order, for each object its
+// properties followed by the properties of its dynamic struct components, every property before
+// the properties nested in it, array elements and map values in order, and the actors of a
+// persistence container by ascending unique id
+
+impl SaveGameArchive {
+    pub fn visit_properties(&self, f: &mut impl FnMut(&Property)) {
+        visit_content(&self.content, f);
+    }
+
+    pub fn visit_properties_mut(&mut self, f: &mut impl FnMut(&mut Property)) {
+        visit_content_mut(&mut self.content, f);
+    }
+}
+
+fn visit_content(content: &SaveGameArchiveContent, f: &mut impl FnMut(&Property)) {
+    for object in &content.object_index {
+        visit_all(&object.properties, f);
+
+        for component in object.components.iter().flatten() {
+            if let ComponentType::DynamicStruct(component) = &component.component_type {
+                visit_all(&component.properties, f);
+            }
+        }
+    }
+}
+
+fn visit_all(properties: &[Property], f: &mut impl FnMut(&Property)) {
+    for property in properties {
+        f(property);
+        visit_data(&property.data, f);
+    }
+}
+
+fn visit_data(data: &PropertyData, f: &mut impl FnMut(&Property)) {
+    match data {
+        PropertyData::Struct(property) => match &property.data {
+            StructData::Dynamic(dynamic_struct) => visit_all(&dynamic_struct.properties, f),
+            StructData::PersistenceBlob(blob) => visit_content(&blob.archive, f),
+            StructData::PersistenceContainer(container) => {
+                let mut actors = container.actors.iter().collect::<Vec<_>>();
+
+                actors.sort_by_key(|(unique_id, _)| **unique_id);
+
+                for (_, actor) in actors {
+                    visit_content(&actor.archive, f);
+                }
+            }
+            _ => {}
+        },
+        PropertyData::Array(array) => {
+            for element in &array.elements {
+                visit_data(element, f);
+            }
+        }
+        PropertyData::Map(map) => {
+            for (_, value) in &map.elements {
+                visit_data(value, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn visit_content_mut(content: &mut SaveGameArchiveContent, f: &mut impl FnMut(&mut Property)) {
+    for object in &mut content.object_index {
+        visit_all_mut(&mut object.properties, f);
+
+        for component in object.components.iter_mut().flatten() {
+            if let ComponentType::DynamicStruct(component) = &mut component.component_type {
+                visit_all_mut(&mut component.properties, f);
+            }
+        }
+    }
+}
+
+fn visit_all_mut(properties: &mut [Property], f: &mut impl FnMut(&mut Property)) {
+    for property in properties {
+        f(property);
+        visit_data_mut(&mut property.data, f);
+    }
+}
+
+fn visit_data_mut(data: &mut PropertyData, f: &mut impl FnMut(&mut Property)) {
+    match data {
+        PropertyData::Struct(property) => match &mut property.data {
+            StructData::Dynamic(dynamic_struct) => visit_all_mut(&mut dynamic_struct.properties, f),
+            StructData::PersistenceBlob(blob) => visit_content_mut(&mut blob.archive, f),
+            StructData::PersistenceContainer(container) => {
+                let mut actors = container.actors.iter_mut().collect::<Vec<_>>();
+
+                actors.sort_by_key(|(unique_id, _)| **unique_id);
+
+                for (_, actor) in actors {
+                    visit_content_mut(&mut actor.archive, f);
+                }
+            }
+            _ => {}
+        },
+        PropertyData::Array(array) => {
+            for element in &mut array.elements {
+                visit_data_mut(element, f);
+            }
+        }
+        PropertyData::Map(map) => {
+            for (_, value) in &mut map.elements {
+                visit_data_mut(value, f);
+            }
+        }
+        _ => {}
+    }
+}
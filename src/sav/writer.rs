@@ -1,12 +1,14 @@
+use std::collections::HashMap;
 use std::cmp::min;
 use std::io::{Cursor, Read, Seek, SeekFrom, Write};
-use byteorder::{LittleEndian, WriteBytesExt};
-use flate2::write::ZlibEncoder;
+use anyhow::bail;
+use byteorder::WriteBytesExt;
+use flate2::write::{GzEncoder, ZlibEncoder};
 use flate2::Compression;
-use crate::io::{Writer, WriterExt};
+use crate::io::{ArchiveWriter, Endian, Reader, Writer, WriterExt, ObjectPadding};
 use crate::properties::Property;
-use crate::sav::{ARCHIVE_V2_HEADER_TAG, Compressor, FCompressedChunkInfo, NameTable, SaveGameArchive, SaveGameArchiveContent, SaveGameArchiveHeader, SavFile, UObject};
-use crate::structs::FName;
+use crate::sav::{ARCHIVE_V2_HEADER_TAG, CHUNK_SIZE, Compressor, FCompressedChunkInfo, NameTable, SaveGameArchive, SaveGameArchiveContent, SaveGameArchiveHeader, SaveVersion, SavFile, UObject};
+use crate::structs::{FName, FTopLevelAssetPath};
 
 impl Compressor {
     fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
@@ -36,13 +38,60 @@ impl Compressor {
     }
 }
 
+impl Compressor {
+    fn compress(&self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let compressed = match self {
+            Compressor::None => data.to_vec(),
+            Compressor::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+
+                encoder.write_all(data)?;
+                encoder.finish()?
+            }
+            Compressor::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+
+                encoder.write_all(data)?;
+                encoder.finish()?
+            }
+            Compressor::LZ4 => lz4_flex::block::compress(data),
+            Compressor::Oodle => bail!("Writing Oodle compressed chunks is not supported"),
+            Compressor::Custom(name) => bail!("Unsupported custom compressor: {}", name),
+        };
+
+        Ok(compressed)
+    }
+}
+
 impl SavFile {
-    #[allow(dead_code)] // TODO: Fix write
+    // the crc32 covers the content size, the save game file version and the archive,
+    // but the archive is stored with its size in place of that version
     pub fn write(
-        mut writer: &mut Writer,
-        archive: &SaveGameArchive
+        writer: &mut Writer,
+        archive: &SaveGameArchive,
+        compressor: &Compressor,
     ) -> anyhow::Result<()> {
-        let mut archive_writer = Writer::new(Vec::new(), 4);
+        SavFile::write_chunks(writer, archive, compressor, None)
+    }
+
+    // copies the chunks whose content is unchanged from `previous` instead of compressing them again,
+    // e.g. when a save manager only edits a few properties of a large save
+    pub fn write_reusing(
+        writer: &mut Writer,
+        archive: &SaveGameArchive,
+        previous: &SavFile,
+    ) -> anyhow::Result<()> {
+        SavFile::write_chunks(writer, archive, previous.compressor(), Some(previous))
+    }
+
+    fn write_chunks(
+        writer: &mut Writer,
+        archive: &SaveGameArchive,
+        compressor: &Compressor,
+        previous: Option<&SavFile>,
+    ) -> anyhow::Result<()> {
+        let capacity = previous.map_or(writer.capacity(), |sav_file| sav_file.content_size as usize);
+        let mut archive_writer = sub_writer_with_capacity(writer, capacity);
 
         // write archive
 
@@ -56,7 +105,7 @@ impl SavFile {
         // update archive header with correct values
 
         archive_writer.seek(SeekFrom::Start(4))?;
-        archive_writer.write_u32::<LittleEndian>(size_with_header as u32)?;
+        archive_writer.write_u32::<Endian>(size_with_header as u32)?;
 
         // calculate crc32 from position 4 to end of file
 
@@ -69,15 +118,17 @@ impl SavFile {
         // fix up the archive header to only contain the size at 0x08
 
         archive_writer.seek(SeekFrom::Start(8))?;
-        archive_writer.write_u32::<LittleEndian>(size as u32 - 4)?;
+        archive_writer.write_u32::<Endian>(size as u32 - 4)?;
 
         // append sav file header
 
-        writer.write_u32::<LittleEndian>(crc32)?;
-        writer.write_u32::<LittleEndian>(size_with_header as u32)?;
-        writer.write_u32::<LittleEndian>(9)?; // todo
+        writer.write_u32::<Endian>(crc32)?;
+        writer.write_u32::<Endian>(size_with_header as u32)?;
+        writer.write_u32::<Endian>(archive.header.save_game_file_version)?;
 
-        let mut buf = vec![0u8; 2 << 16];
+        let mut buf = vec![0u8; CHUNK_SIZE as usize];
+        let mut previous_chunks = previous.map(|sav_file| sav_file.chunks.iter());
+        let mut previous_content = Vec::with_capacity(CHUNK_SIZE as usize);
         let mut to_write = size;
 
         let mut archive_writer = Cursor::new(archive_writer.into_inner());
@@ -87,9 +138,9 @@ impl SavFile {
         while to_write > 0 {
             let chunk_size = min(to_write, buf.len() as u64);
 
-            writer.write_u64::<LittleEndian>(ARCHIVE_V2_HEADER_TAG)?;
-            writer.write_u64::<LittleEndian>(2 << 16)?;
-            Compressor::Zlib.write(writer)?;
+            writer.write_u64::<Endian>(ARCHIVE_V2_HEADER_TAG)?;
+            writer.write_u64::<Endian>(CHUNK_SIZE)?;
+            compressor.write(writer)?;
 
             let mut compression_info = FCompressedChunkInfo {
                 compressed_size: 0, // placeholder
@@ -102,12 +153,20 @@ impl SavFile {
             compression_info.write(writer)?;
 
             let start_pos = writer.position();
-            let mut encoder = ZlibEncoder::new(writer, Compression::default());
 
             archive_writer.read_exact(&mut buf[..chunk_size as usize])?;
-            encoder.write_all(&buf[..chunk_size as usize])?;
 
-            writer = encoder.finish()?;
+            let chunk = &buf[..chunk_size as usize];
+            let previous_chunk = previous_chunks.as_mut().and_then(|chunks| chunks.next());
+
+            previous_content.clear();
+
+            match previous_chunk {
+                Some(previous_chunk) if previous_chunk.decompress(&mut previous_content).is_ok() && previous_content == chunk => {
+                    writer.write_all(&previous_chunk.compressed_data)?;
+                }
+                _ => writer.write_all(&compressor.compress(chunk)?)?,
+            }
 
             let compressed_size = writer.position() - start_pos;
             let current_pos = writer.position();
@@ -126,49 +185,133 @@ impl SavFile {
 
         Ok(())
     }
+
+    // re-reads the written save and only emits it if it matches the source archive
+    pub fn write_verified(
+        writer: &mut Writer,
+        archive: &SaveGameArchive,
+        compressor: &Compressor,
+    ) -> anyhow::Result<()> {
+        let mut sav_writer = sub_writer_with_capacity(writer, writer.capacity());
+
+        SavFile::write(&mut sav_writer, archive, compressor)?;
+
+        let data = sav_writer.into_inner();
+
+        SavFile::verify(&data)?;
+
+        let mut reader = Reader::new(data.clone(), ObjectPadding::Four);
+        let raw_fallback = archive.has_raw_properties();
+
+        // raw properties only read back as raw data again with the option they were read with
+        let written = SavFile::read(&mut reader)
+            .and_then(|sav_file| sav_file.get_content())
+            .and_then(|content| {
+                let mut reader = Reader::new(content, ObjectPadding::Four);
+
+                reader.raw_fallback = raw_fallback;
+                SaveGameArchive::read(&mut reader)
+            })
+            .map_err(|e| anyhow::anyhow!("Written save can't be read back: {}", e))?;
+
+        if !written.semantic_eq(archive)? {
+            bail!("Written save does not match the source archive");
+        }
+
+        writer.write_all(&data)?;
+
+        Ok(())
+    }
+
+    // checks the header of a written save against its content, this is what the game validates on load
+    pub fn verify(data: &[u8]) -> anyhow::Result<()> {
+        let sav_file = SavFile::read(&mut Reader::new(data.to_vec(), ObjectPadding::Four))?;
+        let content = sav_file.get_content()
+            .map_err(|e| anyhow::anyhow!("Written save fails its checksum: {}", e))?;
+
+        if content.len() != sav_file.content_size as usize {
+            bail!(
+                "Written save has a content size of {}, but its content is {} bytes",
+                sav_file.content_size,
+                content.len(),
+            );
+        }
+
+        Ok(())
+    }
 }
 
 impl FCompressedChunkInfo {
     fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
-        writer.write_u64::<LittleEndian>(self.compressed_size)?;
-        writer.write_u64::<LittleEndian>(self.uncompressed_size)?;
+        writer.write_u64::<Endian>(self.compressed_size)?;
+        writer.write_u64::<Endian>(self.uncompressed_size)?;
 
         Ok(())
     }
 }
 
 impl SaveGameArchiveHeader {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
-        writer.write_u32::<LittleEndian>(0)?;
-        writer.write_u32::<LittleEndian>(0)?;
-        writer.write_u32::<LittleEndian>(self.save_game_file_version)?;
-        writer.write_u32::<LittleEndian>(self.build_number)?;
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u32::<Endian>(0)?;
+        writer.write_u32::<Endian>(0)?;
+        writer.write_u32::<Endian>(self.save_game_file_version)?;
+        writer.write_u32::<Endian>(self.build_number)?;
+
+        // unknown versions are written in the newest known layout, reading them back rejects them
+        writer.set_save_version(SaveVersion::detect(self).unwrap_or_default());
 
         Ok(())
     }
 }
 
+// the high bit of a written index flags a numbered name
+const MAX_NAME_INDEX: usize = 0x7FFF;
+
 impl NameTable {
-    pub fn write_name(&mut self, writer: &mut Writer, name: &FName) -> anyhow::Result<()> {
+    // builds the lookup once, duplicate names in `list` resolve to their first index. read tables
+    // are built with it as well, so writing into them reuses the names they already have
+    pub fn new(list: Vec<String>) -> NameTable {
+        let mut lookup = HashMap::with_capacity(list.len());
+
+        for (index, name) in list.iter().enumerate().take(MAX_NAME_INDEX + 1) {
+            lookup.entry(name.clone()).or_insert(index as u16);
+        }
+
+        NameTable { list, lookup }
+    }
+
+    pub fn write_name<W: ArchiveWriter>(&mut self, writer: &mut W, name: &FName) -> anyhow::Result<()> {
         const HAS_NUMBER: u16 = 1 << 15;
 
         // check if name is already in table (insert if not)
-        let index = self.list.iter().position(|n| *n == name.value);
-        let mut index = if let Some(index) = index {
-            index as u16
-        } else {
-            self.list.push(name.value.clone());
-            (self.list.len() - 1) as u16
+        let mut index = match self.lookup.get(&name.value) {
+            Some(index) => *index,
+            None => {
+                if self.list.len() > MAX_NAME_INDEX {
+                    bail!(
+                        "Name table overflow: {} can't be added, at most {} unique names can be written",
+                        name.value,
+                        MAX_NAME_INDEX + 1,
+                    );
+                }
+
+                let index = self.list.len() as u16;
+
+                self.list.push(name.value.clone());
+                self.lookup.insert(name.value.clone(), index);
+
+                index
+            }
         };
 
         if name.number.is_some() {
             index |= HAS_NUMBER;
         }
 
-        writer.write_u16::<LittleEndian>(index)?;
+        writer.write_u16::<Endian>(index)?;
 
         if let Some(number) = name.number {
-            writer.write_u32::<LittleEndian>(number)?;
+            writer.write_u32::<Endian>(number)?;
         }
 
         Ok(())
@@ -176,10 +319,14 @@ impl NameTable {
 }
 
 impl SaveGameArchiveContent {
-    pub fn write(
+    pub fn write<W: ArchiveWriter>(
         &self,
-        writer: &mut Writer,
+        writer: &mut W,
     ) -> anyhow::Result<()> {
+        if self.partial {
+            bail!("Refusing to write a partially recovered archive");
+        }
+
         if let Some(package_version) = &self.package_version {
             package_version.write(writer)?;
         }
@@ -189,37 +336,43 @@ impl SaveGameArchiveContent {
         }
 
         let name_table_offset = writer.position();
-        writer.write_u64::<LittleEndian>(0)?; // placeholder
+        writer.write_u64::<Endian>(0)?; // placeholder
 
-        writer.write_u32::<LittleEndian>(self.version)?;
+        writer.write_u32::<Endian>(self.version)?;
 
         let object_index_offset = writer.position();
-        writer.write_u64::<LittleEndian>(0)?; // placeholder
+        writer.write_u64::<Endian>(0)?; // placeholder
 
-        let mut name_table = NameTable { list: Vec::new() };
+        // preserving the table keeps the indices (and duplicates) of the original names, but uses of a
+        // later duplicate are written with the first index, otherwise it is rebuilt from the names that are written
+        let mut name_table = if writer.preserve_name_table() {
+            NameTable::new(self.name_table.list.clone())
+        } else {
+            NameTable::default()
+        };
 
         for object in &self.object_index {
-            writer.write_u32::<LittleEndian>(object.object_id)?;
+            writer.write_u32::<Endian>(object.object_id)?;
             object.write_data(writer, &mut name_table)?;
         }
 
         let object_index_offset_start = writer.position();
 
         writer.seek(SeekFrom::Start(object_index_offset))?;
-        writer.write_u64::<LittleEndian>(object_index_offset_start)?;
+        writer.write_u64::<Endian>(object_index_offset_start)?;
         writer.seek(SeekFrom::Start(object_index_offset_start))?;
-        writer.write_u32::<LittleEndian>(self.object_index.len() as u32)?;
+        writer.write_u32::<Endian>(self.object_index.len() as u32)?;
 
         for object in &self.object_index {
-            object.write(writer, &mut name_table)?;
+            object.write(writer, &mut name_table, self.save_game_class_path.as_ref())?;
         }
 
         let name_table_offset_start = writer.position();
 
         writer.seek(SeekFrom::Start(name_table_offset))?;
-        writer.write_u64::<LittleEndian>(name_table_offset_start)?;
+        writer.write_u64::<Endian>(name_table_offset_start)?;
         writer.seek(SeekFrom::Start(name_table_offset_start))?;
-        writer.write_u32::<LittleEndian>(name_table.list.len() as u32)?;
+        writer.write_u32::<Endian>(name_table.list.len() as u32)?;
 
         for name in &name_table.list {
             writer.write_fstring(name.clone())?;
@@ -230,43 +383,66 @@ impl SaveGameArchiveContent {
 }
 
 impl UObject {
-    pub fn write(&self, writer: &mut Writer, name_table: &mut NameTable) -> anyhow::Result<()> {
+    pub fn write<W: ArchiveWriter>(
+        &self,
+        writer: &mut W,
+        name_table: &mut NameTable,
+        save_game_class_path: Option<&FTopLevelAssetPath>,
+    ) -> anyhow::Result<()> {
         writer.write_u8(self.was_loaded as u8)?;
-        writer.write_fstring(self.object_path.clone())?;
 
-        if let Some(loaded_data) = &self.loaded_data {
-            name_table.write_name(writer, &loaded_data.name)?;
-            writer.write_u32::<LittleEndian>(loaded_data.outer_id)?;
+        // the reader takes the path of a loaded object 0 from the class path
+        match save_game_class_path {
+            Some(class_path) if self.was_loaded && self.object_id == 0 => {
+                if class_path.path != self.object_path {
+                    bail!(
+                        "Object 0 path {} does not match the save game class path {}",
+                        self.object_path,
+                        class_path.path,
+                    );
+                }
+            }
+            _ => writer.write_fstring(self.object_path.clone())?,
+        }
+
+        // the reader only expects loaded data where the save version stores it
+        match (&self.loaded_data, writer.save_version().has_loaded_data(self.was_loaded)) {
+            (Some(loaded_data), true) => {
+                name_table.write_name(writer, &loaded_data.name)?;
+                writer.write_u32::<Endian>(loaded_data.outer_id)?;
+            }
+            (None, false) => {}
+            (Some(_), false) => bail!("Object {} was loaded but has loaded data", self.object_path),
+            (None, true) => bail!("Object {} wasn't loaded but has no loaded data", self.object_path),
         }
 
         Ok(())
     }
 
-    pub fn write_data(&self, writer: &mut Writer, name_table: &mut NameTable) -> anyhow::Result<()> {
+    pub fn write_data<W: ArchiveWriter>(&self, writer: &mut W, name_table: &mut NameTable) -> anyhow::Result<()> {
         let size_offset = writer.position();
-        writer.write_u32::<LittleEndian>(0)?; // placeholder for size
+        writer.write_u32::<Endian>(0)?; // placeholder for size
 
         let start_pos = writer.position();
 
-        if !self.properties.is_empty() {
+        // a non-empty object always starts with a property list when it's read
+        if !self.properties.is_empty() || self.empty_property_list || !self.trailing_data.is_empty() {
             for property in &self.properties {
                 property.write(writer, name_table)?;
             }
 
             Property::write_none(writer, name_table)?;
 
-            if self.object_id == 0 && writer.object_padding == 8 {
-                writer.write_u64::<LittleEndian>(0)?;
-            } else {
-                writer.write_u32::<LittleEndian>(0)?;
-            }
+            writer.write_uint::<Endian>(0, writer.save_version().terminator_size(writer.object_padding(), self.object_id))?;
         }
 
+        writer.write_all(&self.trailing_data)?;
+
         let end_pos = writer.position();
         let size = end_pos - start_pos;
 
         writer.seek(SeekFrom::Start(size_offset))?;
-        writer.write_u32::<LittleEndian>(size as u32)?;
+        writer.write_u32::<Endian>(size as u32)?;
         writer.seek(SeekFrom::Start(end_pos))?;
 
         self.write_components(writer, name_table)?;
@@ -274,16 +450,16 @@ impl UObject {
         Ok(())
     }
 
-    pub fn write_components(&self, writer: &mut Writer, name_table: &mut NameTable) -> anyhow::Result<()> {
+    pub fn write_components<W: ArchiveWriter>(&self, writer: &mut W, name_table: &mut NameTable) -> anyhow::Result<()> {
         if let Some(components) = &self.components {
             writer.write_u8(1)?;
-            writer.write_u32::<LittleEndian>(components.len() as u32)?;
+            writer.write_u32::<Endian>(components.len() as u32)?;
 
             for component in components {
                 writer.write_fstring(component.component_key.clone())?;
 
                 let size_offset = writer.position();
-                writer.write_u32::<LittleEndian>(0)?; // placeholder for size
+                writer.write_u32::<Endian>(0)?; // placeholder for size
 
                 let start_pos = writer.position();
 
@@ -293,7 +469,7 @@ impl UObject {
                 let size = end_pos - start_pos;
 
                 writer.seek(SeekFrom::Start(size_offset))?;
-                writer.write_u32::<LittleEndian>(size as u32)?;
+                writer.write_u32::<Endian>(size as u32)?;
                 writer.seek(SeekFrom::Start(end_pos))?;
             }
         } else {
@@ -304,3 +480,12 @@ impl UObject {
     }
 }
 
+// the archive is written and verified in buffers of its own, which get the capacity reserved for the output
+fn sub_writer_with_capacity(writer: &Writer, capacity: usize) -> Writer {
+    let mut sub_writer = Writer::with_capacity(capacity, ObjectPadding::Four);
+
+    sub_writer.preserve_name_table = writer.preserve_name_table;
+    sub_writer.save_version = writer.save_version;
+
+    sub_writer
+}
@@ -0,0 +1,93 @@
+use crate::properties::PropertyData;
+use crate::sav::SaveGameArchive;
+use crate::structs::{FGuid, FName, StructData};
+
+// properties that identify the player's account or platform
+const DEFAULT_SCRUBBED_PROPERTIES: [&str; 5] = [
+    "AccountId",
+    "AccountGuid",
+    "PlatformId",
+    "PlatformName",
+    "UserId",
+];
+
+// additionally scrubbed when anonymizing, i.e. names other players would recognize
+const DEFAULT_ANONYMIZED_PROPERTIES: [&str; 6] = [
+    "CharacterName",
+    "PlayerName",
+    "DisplayName",
+    "UserName",
+    "SteamId",
+    "OnlineId",
+];
+
+const ANONYMIZED_PLACEHOLDER: &str = "Anonymous";
+
+// replaces strings and names with the placeholder (blanks them by default), zeroes 64-bit ids
+// and randomizes guids of the listed properties, wherever they appear, while leaving the
+// layout of the save untouched
+#[derive(Debug)]
+pub struct Scrubber {
+    pub properties: Vec<String>,
+    pub placeholder: String,
+}
+
+impl Default for Scrubber {
+    fn default() -> Self {
+        Scrubber {
+            properties: DEFAULT_SCRUBBED_PROPERTIES.iter().map(|name| name.to_string()).collect(),
+            placeholder: String::new(),
+        }
+    }
+}
+
+impl Scrubber {
+    pub fn anonymizer() -> Scrubber {
+        let mut scrubber = Scrubber::default();
+
+        scrubber.properties.extend(DEFAULT_ANONYMIZED_PROPERTIES.iter().map(|name| name.to_string()));
+        scrubber.placeholder = ANONYMIZED_PLACEHOLDER.to_owned();
+
+        scrubber
+    }
+
+    // returns the number of scrubbed properties
+    pub fn scrub(&self, archive: &mut SaveGameArchive) -> usize {
+        let mut count = 0;
+
+        archive.visit_properties_mut(&mut |property| {
+            if self.properties.contains(&property.name.value) && self.blank(&mut property.data) {
+                count += 1;
+            }
+        });
+
+        count
+    }
+
+    fn blank(&self, data: &mut PropertyData) -> bool {
+        match data {
+            PropertyData::Str(value) => *value = self.placeholder.clone(),
+            PropertyData::Name(name) => *name = match self.placeholder.as_str() {
+                "" => FName::none(),
+                placeholder => FName::from(placeholder),
+            },
+            PropertyData::Int64(value) => *value = 0,
+            PropertyData::UInt64(value) => *value = 0,
+            PropertyData::Struct(property) => match &mut property.data {
+                StructData::Guid(guid) => *guid = FGuid::random(),
+                _ => return false,
+            },
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl SaveGameArchive {
+    // scrubs account ids and player names so the save can be shared, sizes are
+    // recomputed when the archive is written
+    pub fn anonymize(&mut self) -> usize {
+        Scrubber::anonymizer().scrub(self)
+    }
+}
@@ -2,10 +2,18 @@ mod reader;
 mod writer;
 
 use crate::properties::Property;
-use serde::{Deserialize, Serialize};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use anyhow::bail;
+use chrono::{Duration, NaiveDate, SecondsFormat, Utc};
 use crate::sav::SaveGameArchiveContent;
 
+// versions sharing the version/index offset/dynamic offset header layout
+const SUPPORTED_PERSISTENCE_CONTAINER_VERSIONS: RangeInclusive<u32> = 1..=4;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FVector {
     pub x: f64,
@@ -21,6 +29,55 @@ pub struct FQuaternion {
     pub z: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FRotator {
+    pub pitch: f64,
+    pub yaw: f64,
+    pub roll: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FIntVector {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FLinearColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FVector2D {
+    pub x: f64,
+    pub y: f64,
+}
+
+// the validity flags are kept as stored, a uint8 for boxes and a 4 byte bool for 2D boxes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FBox {
+    pub min: FVector,
+    pub max: FVector,
+    pub is_valid: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FBox2D {
+    pub min: FVector2D,
+    pub max: FVector2D,
+    pub is_valid: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FSphere {
+    pub center: FVector,
+    pub w: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FTransform {
     pub rotation: FQuaternion,
@@ -28,7 +85,8 @@ pub struct FTransform {
     pub scale: FVector,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+// serialized as its string form, see the serde impls below
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct FGuid {
     pub a: u32,
     pub b: u32,
@@ -75,12 +133,20 @@ pub struct PersistenceContainer {
     pub actors: HashMap<u64, Actor>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
+pub struct PersistenceContainerStats {
+    pub actor_count: usize,
+    pub destroyed_count: usize,
+    pub orphaned_destroyed: Vec<u64>, // destroyed ids without a matching actor
+}
+
+// both are stored as ticks and serialized as text, see the serde impls below
+#[derive(Debug)]
 pub struct Timespan {
     pub value: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
 pub struct DateTime {
     pub value: u64,
 }
@@ -100,10 +166,18 @@ pub enum StructData {
     Timespan(Timespan),
     DateTime(DateTime),
     Vector(FVector),
+    Rotator(FRotator),
+    Quat(FQuaternion),
+    IntVector(FIntVector),
+    LinearColor(FLinearColor),
+    Box(FBox),
+    Box2D(FBox2D),
+    Sphere(FSphere),
     Dynamic(DynamicStruct),
+    RawBlob(Vec<u8>), // an unparsed PersistenceBlob, see Reader::raw_blobs
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct FName {
     pub value: String,
     #[serde(skip_serializing_if = "Option::is_none", default = "Option::default")]
@@ -118,6 +192,41 @@ pub struct FPackageVersion {
 
 // utility functions
 
+impl PersistenceContainer {
+    pub fn stats(&self) -> PersistenceContainerStats {
+        let orphaned_destroyed = self.destroyed.iter()
+            .filter(|unique_id| !self.actors.contains_key(unique_id))
+            .copied()
+            .collect();
+
+        PersistenceContainerStats {
+            actor_count: self.actors.len(),
+            destroyed_count: self.destroyed.len(),
+            orphaned_destroyed,
+        }
+    }
+
+    // only dynamic actors (spawned at runtime, e.g. dropped items) store their class
+    pub fn actors_by_class<'a>(&'a self, class_path: &'a str) -> impl Iterator<Item = (&'a u64, &'a Actor)> {
+        self.actors.iter().filter(move |(_, actor)| {
+            actor.dynamic_data.as_ref().is_some_and(|dynamic_data| dynamic_data.class_path.path == class_path)
+        })
+    }
+
+    fn check_version(version: u32) -> anyhow::Result<()> {
+        if !SUPPORTED_PERSISTENCE_CONTAINER_VERSIONS.contains(&version) {
+            bail!(
+                "Unsupported persistence container version: {} (supported: {}-{})",
+                version,
+                SUPPORTED_PERSISTENCE_CONTAINER_VERSIONS.start(),
+                SUPPORTED_PERSISTENCE_CONTAINER_VERSIONS.end(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
 impl FName {
     pub fn from(name: &str) -> FName {
         FName {
@@ -129,4 +238,248 @@ impl FName {
     pub fn none() -> FName {
         FName::from("None")
     }
+
+    // numbered names are displayed as `<value>_<number>`
+    pub fn to_display_string(&self) -> String {
+        match self.number {
+            Some(number) => format!("{}_{}", self.value, number),
+            None => self.value.clone(),
+        }
+    }
+
+    // `Foo` matches `Foo` with any number, `Foo_3` matches `Foo` numbered 3 (or a literal `Foo_3`)
+    pub fn matches(&self, name: &str) -> bool {
+        self.value == name || (self.number.is_some() && self.to_display_string() == name)
+    }
+}
+
+impl fmt::Display for FName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_display_string())
+    }
+}
+
+// the inverse of to_display_string, a `_<digits>` suffix without leading zeros is the number
+impl FromStr for FName {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = s.rsplit_once('_').and_then(|(value, suffix)| {
+            let is_number = !value.is_empty()
+                && !suffix.is_empty()
+                && suffix.chars().all(|c| c.is_ascii_digit())
+                && (suffix == "0" || !suffix.starts_with('0'));
+
+            match is_number {
+                true => suffix.parse::<u32>().ok().map(|number| (value, number)),
+                false => None,
+            }
+        });
+
+        Ok(match number {
+            Some((value, number)) => FName { value: value.to_owned(), number: Some(number) },
+            None => FName::from(s),
+        })
+    }
+}
+
+// ticks are 100ns intervals since 0001-01-01T00:00:00
+const TICKS_PER_SECOND: u64 = 10_000_000;
+
+impl DateTime {
+    fn epoch() -> chrono::DateTime<Utc> {
+        NaiveDate::from_ymd_opt(1, 1, 1)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .expect("valid epoch")
+            .and_utc()
+    }
+
+    pub fn to_chrono(&self) -> Option<chrono::DateTime<Utc>> {
+        let seconds = (self.value / TICKS_PER_SECOND) as i64;
+        let nanos = (self.value % TICKS_PER_SECOND) as i64 * 100;
+
+        Self::epoch().checked_add_signed(Duration::seconds(seconds) + Duration::nanoseconds(nanos))
+    }
+
+    pub fn from_chrono(value: chrono::DateTime<Utc>) -> anyhow::Result<DateTime> {
+        let duration = value - Self::epoch();
+        let seconds = u64::try_from(duration.num_seconds())?;
+        let sub_nanos = (duration - Duration::seconds(duration.num_seconds()))
+            .num_nanoseconds()
+            .unwrap_or(0) as u64;
+
+        let value = seconds
+            .checked_mul(TICKS_PER_SECOND)
+            .and_then(|ticks| ticks.checked_add(sub_nanos / 100))
+            .ok_or_else(|| anyhow::anyhow!("DateTime out of range: {}", value))?;
+
+        Ok(DateTime { value })
+    }
+}
+
+// `HH:MM:SS`, with the remaining ticks as `.fffffff` if there are any. hours aren't wrapped into days
+impl fmt::Display for Timespan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.value / TICKS_PER_SECOND;
+        let ticks = self.value % TICKS_PER_SECOND;
+
+        write!(f, "{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)?;
+
+        if ticks != 0 {
+            write!(f, ".{:07}", ticks)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Timespan {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (time, ticks) = s.split_once('.').unwrap_or((s, "0"));
+        let parts = time.split(':').collect::<Vec<_>>();
+
+        let is_digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+
+        if parts.len() != 3 || !parts.iter().all(|part| is_digits(part)) || !is_digits(ticks) || ticks.len() > 7 {
+            bail!("Invalid timespan: {} (expected HH:MM:SS[.fffffff])", s);
+        }
+
+        let hours = parts[0].parse::<u64>()?;
+        let minutes = parts[1].parse::<u64>()?;
+        let seconds = parts[2].parse::<u64>()?;
+
+        if minutes >= 60 || seconds >= 60 {
+            bail!("Invalid timespan: {} (minutes and seconds must be below 60)", s);
+        }
+
+        // `.5` is half a second, so the fraction is padded to whole ticks
+        let ticks = format!("{:0<7}", ticks).parse::<u64>()?;
+
+        let value = hours
+            .checked_mul(3600)
+            .and_then(|total| total.checked_add(minutes * 60 + seconds))
+            .and_then(|total| total.checked_mul(TICKS_PER_SECOND))
+            .and_then(|total| total.checked_add(ticks))
+            .ok_or_else(|| anyhow::anyhow!("Timespan out of range: {}", s))?;
+
+        Ok(Timespan { value })
+    }
+}
+
+impl FGuid {
+    pub fn random() -> FGuid {
+        FGuid {
+            a: rand::random(),
+            b: rand::random(),
+            c: rand::random(),
+            d: rand::random(),
+        }
+    }
+}
+
+impl fmt::Display for FGuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:08X}-{:04X}-{:04X}-{:04X}-{:04X}{:08X}",
+            self.a,
+            self.b >> 16,
+            self.b & 0xFFFF,
+            self.c >> 16,
+            self.c & 0xFFFF,
+            self.d,
+        )
+    }
+}
+
+impl FromStr for FGuid {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.replace('-', "");
+
+        if digits.len() != 32 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("Invalid guid: {}", s);
+        }
+
+        let part = |i: usize| u32::from_str_radix(&digits[i * 8..(i + 1) * 8], 16);
+
+        Ok(FGuid {
+            a: part(0)?,
+            b: part(1)?,
+            c: part(2)?,
+            d: part(3)?,
+        })
+    }
+}
+
+impl Serialize for FGuid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// JSON files written before guids were strings store the four fields
+impl<'de> Deserialize<'de> for FGuid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum GuidRepr {
+            String(String),
+            Fields { a: u32, b: u32, c: u32, d: u32 },
+        }
+
+        match GuidRepr::deserialize(deserializer)? {
+            GuidRepr::String(guid) => guid.parse().map_err(de::Error::custom),
+            GuidRepr::Fields { a, b, c, d } => Ok(FGuid { a, b, c, d }),
+        }
+    }
+}
+
+// ISO-8601 in UTC, with as many fractional digits as the ticks need
+impl Serialize for DateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let date_time = self.to_chrono()
+            .ok_or_else(|| ser::Error::custom(format!("DateTime out of range: {}", self.value)))?;
+
+        serializer.serialize_str(&date_time.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+}
+
+impl<'de> Deserialize<'de> for DateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match TicksRepr::deserialize(deserializer)? {
+            // not parse_from_rfc3339, which rejects the `+YYYYYY` years far past 9999 are written with
+            TicksRepr::String(date_time) => date_time.parse::<chrono::DateTime<chrono::FixedOffset>>()
+                .map_err(|e| de::Error::custom(format!("Invalid DateTime {}: {}", date_time, e)))
+                .and_then(|date_time| DateTime::from_chrono(date_time.with_timezone(&Utc)).map_err(de::Error::custom)),
+            TicksRepr::Ticks(value) | TicksRepr::Fields { value } => Ok(DateTime { value }),
+        }
+    }
+}
+
+impl Serialize for Timespan {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timespan {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match TicksRepr::deserialize(deserializer)? {
+            TicksRepr::String(timespan) => timespan.parse().map_err(de::Error::custom),
+            TicksRepr::Ticks(value) | TicksRepr::Fields { value } => Ok(Timespan { value }),
+        }
+    }
+}
+
+// JSON files written before DateTime and Timespan were text store a `value` field with the ticks
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TicksRepr {
+    String(String),
+    Ticks(u64),
+    Fields { value: u64 },
 }
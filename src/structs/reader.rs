@@ -1,16 +1,16 @@
-use crate::io::{Reader, ReaderExt};
+use crate::io::{ArchiveReader, Endian, ObjectPadding};
 use crate::properties::Property;
-use crate::structs::{Actor, DateTime, DynamicActor, DynamicStruct, FGuid, FInfo, FPackageVersion, FQuaternion, FTopLevelAssetPath, FTransform, FVector, PersistenceBlob, PersistenceContainer, Timespan};
-use byteorder::{LittleEndian, ReadBytesExt};
+use crate::structs::{Actor, DateTime, DynamicActor, DynamicStruct, FBox, FBox2D, FGuid, FInfo, FIntVector, FLinearColor, FPackageVersion, FQuaternion, FRotator, FSphere, FTopLevelAssetPath, FTransform, FVector, FVector2D, PersistenceBlob, PersistenceContainer, Timespan};
+use anyhow::{anyhow, Context};
+use byteorder::ReadBytesExt;
 use std::collections::HashMap;
-use std::io::{Read, Seek, SeekFrom};
 use crate::sav::SaveGameArchiveContent;
 
 impl FVector {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<Self> {
-        let x = reader.read_f64::<LittleEndian>()?;
-        let y = reader.read_f64::<LittleEndian>()?;
-        let z = reader.read_f64::<LittleEndian>()?;
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let x = reader.read_f64::<Endian>()?;
+        let y = reader.read_f64::<Endian>()?;
+        let z = reader.read_f64::<Endian>()?;
 
         let vector = FVector { x, y, z };
 
@@ -19,11 +19,11 @@ impl FVector {
 }
 
 impl FQuaternion {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<Self> {
-        let w = reader.read_f64::<LittleEndian>()?;
-        let x = reader.read_f64::<LittleEndian>()?;
-        let y = reader.read_f64::<LittleEndian>()?;
-        let z = reader.read_f64::<LittleEndian>()?;
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let w = reader.read_f64::<Endian>()?;
+        let x = reader.read_f64::<Endian>()?;
+        let y = reader.read_f64::<Endian>()?;
+        let z = reader.read_f64::<Endian>()?;
 
         let quaternion = FQuaternion { w, x, y, z };
 
@@ -31,8 +31,79 @@ impl FQuaternion {
     }
 }
 
+impl FRotator {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let pitch = reader.read_f64::<Endian>()?;
+        let yaw = reader.read_f64::<Endian>()?;
+        let roll = reader.read_f64::<Endian>()?;
+
+        let rotator = FRotator { pitch, yaw, roll };
+
+        Ok(rotator)
+    }
+}
+
+impl FIntVector {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let x = reader.read_i32::<Endian>()?;
+        let y = reader.read_i32::<Endian>()?;
+        let z = reader.read_i32::<Endian>()?;
+
+        Ok(FIntVector { x, y, z })
+    }
+}
+
+impl FLinearColor {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let r = reader.read_f32::<Endian>()?;
+        let g = reader.read_f32::<Endian>()?;
+        let b = reader.read_f32::<Endian>()?;
+        let a = reader.read_f32::<Endian>()?;
+
+        Ok(FLinearColor { r, g, b, a })
+    }
+}
+
+impl FVector2D {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let x = reader.read_f64::<Endian>()?;
+        let y = reader.read_f64::<Endian>()?;
+
+        Ok(FVector2D { x, y })
+    }
+}
+
+impl FBox {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let min = FVector::read(reader)?;
+        let max = FVector::read(reader)?;
+        let is_valid = reader.read_u8()?;
+
+        Ok(FBox { min, max, is_valid })
+    }
+}
+
+impl FBox2D {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let min = FVector2D::read(reader)?;
+        let max = FVector2D::read(reader)?;
+        let is_valid = reader.read_u32::<Endian>()?;
+
+        Ok(FBox2D { min, max, is_valid })
+    }
+}
+
+impl FSphere {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let center = FVector::read(reader)?;
+        let w = reader.read_f64::<Endian>()?;
+
+        Ok(FSphere { center, w })
+    }
+}
+
 impl FTransform {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<Self> {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
         let rotation = FQuaternion::read(reader)?;
         let position = FVector::read(reader)?;
         let scale = FVector::read(reader)?;
@@ -48,18 +119,18 @@ impl FTransform {
 }
 
 impl FGuid {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<FGuid> {
-        let a = reader.read_u32::<LittleEndian>()?;
-        let b = reader.read_u32::<LittleEndian>()?;
-        let c = reader.read_u32::<LittleEndian>()?;
-        let d = reader.read_u32::<LittleEndian>()?;
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<FGuid> {
+        let a = reader.read_u32::<Endian>()?;
+        let b = reader.read_u32::<Endian>()?;
+        let c = reader.read_u32::<Endian>()?;
+        let d = reader.read_u32::<Endian>()?;
 
         Ok(FGuid { a, b, c, d })
     }
 }
 
 impl FTopLevelAssetPath {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<Self> {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
         let path = reader.read_fstring()?;
         let name = reader.read_fstring()?;
 
@@ -68,10 +139,10 @@ impl FTopLevelAssetPath {
 }
 
 impl FInfo {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<FInfo> {
-        let unique_id = reader.read_u64::<LittleEndian>()?;
-        let offset = reader.read_u32::<LittleEndian>()?;
-        let size = reader.read_u32::<LittleEndian>()?;
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<FInfo> {
+        let unique_id = reader.read_u64::<Endian>()?;
+        let offset = reader.read_u32::<Endian>()?;
+        let size = reader.read_u32::<Endian>()?;
 
         Ok(FInfo {
             unique_id,
@@ -82,8 +153,8 @@ impl FInfo {
 }
 
 impl DynamicActor {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<DynamicActor> {
-        let unique_id = reader.read_u64::<LittleEndian>()?;
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<DynamicActor> {
+        let unique_id = reader.read_u64::<Endian>()?;
         let transform = FTransform::read(reader)?;
         let class_path = FTopLevelAssetPath::read(reader)?;
 
@@ -96,8 +167,8 @@ impl DynamicActor {
 }
 
 impl Actor {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<Actor> {
-        let has_transform = reader.read_u32::<LittleEndian>()?;
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Actor> {
+        let has_transform = reader.read_u32::<Endian>()?;
         let transform = if has_transform != 0 {
             let transform = FTransform::read(reader)?;
 
@@ -117,7 +188,7 @@ impl Actor {
 }
 
 impl PersistenceBlob {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<PersistenceBlob> {
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<PersistenceBlob> {
         let archive = SaveGameArchiveContent::read(reader, true, false)?;
 
         Ok(PersistenceBlob { archive })
@@ -125,15 +196,20 @@ impl PersistenceBlob {
 }
 
 impl PersistenceContainer {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<PersistenceContainer> {
-        let version = reader.read_u32::<LittleEndian>()?;
-        let index_offset = reader.read_u32::<LittleEndian>()?;
-        let dynamic_offset = reader.read_u32::<LittleEndian>()?;
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<PersistenceContainer> {
+        let version = reader.read_u32::<Endian>()?;
 
-        reader.seek(SeekFrom::Start(index_offset as u64))?;
+        PersistenceContainer::check_version(version)?;
 
-        let info_count = reader.read_u32::<LittleEndian>()?;
-        let mut actor_info = Vec::with_capacity(info_count as usize);
+        let index_offset = reader.read_u32::<Endian>()?;
+        let dynamic_offset = reader.read_u32::<Endian>()?;
+
+        // the index holds the actor infos followed by the destroyed list,
+        // the dynamic actors are expected to start right after it
+        reader.seek_checked(index_offset as u64).context("Invalid persistence container index offset")?;
+
+        let info_count = reader.read_u32::<Endian>()?;
+        let mut actor_info = Vec::with_capacity(reader.capacity_for(info_count));
 
         for _ in 0..info_count {
             let info = FInfo::read(reader)?;
@@ -141,36 +217,49 @@ impl PersistenceContainer {
             actor_info.push(info);
         }
 
-        let destroyed_count = reader.read_u32::<LittleEndian>()?;
-        let mut destroyed = Vec::with_capacity(destroyed_count as usize);
+        let destroyed_count = reader.read_u32::<Endian>()?;
+        let mut destroyed = Vec::with_capacity(reader.capacity_for(destroyed_count));
 
         for _ in 0..destroyed_count {
-            let unique_id = reader.read_u64::<LittleEndian>()?;
+            let unique_id = reader.read_u64::<Endian>()?;
 
             destroyed.push(unique_id);
         }
 
-        let mut actors = HashMap::with_capacity(info_count as usize);
+        if reader.position() != dynamic_offset as u64 {
+            println!(
+                "[WARN] Persistence container index ends at {}, but dynamic actors start at {}",
+                reader.position(),
+                dynamic_offset,
+            );
+        }
+
+        let mut actors = HashMap::with_capacity(actor_info.len());
 
         for info in actor_info {
-            let mut bytes = vec![0; info.size as usize];
+            reader.seek_checked(info.offset as u64)
+                .with_context(|| format!("Invalid offset of actor {}", info.unique_id))?;
 
-            reader.seek(SeekFrom::Start(info.offset as u64))?;
+            let mut bytes = vec![0; reader.check_length(info.size as u64)?];
             reader.read_exact(&mut bytes)?;
 
-            let mut sub_reader = Reader::new(bytes, 8);
-            let actor = Actor::read(&mut sub_reader)?;
+            // offsets within the actor are relative to its start
+            let mut sub_reader = reader.sub_reader(bytes, ObjectPadding::Eight);
+            let actor = Actor::read(&mut sub_reader)
+                .with_context(|| format!("while parsing actor {} at offset {:#X}", info.unique_id, info.offset))?;
 
             actors.insert(info.unique_id, actor);
         }
 
-        reader.seek(SeekFrom::Start(dynamic_offset as u64))?;
+        reader.seek_checked(dynamic_offset as u64).context("Invalid dynamic actor offset")?;
 
-        let dynamic_actor_count = reader.read_u32::<LittleEndian>()?;
+        let dynamic_actor_count = reader.read_u32::<Endian>()?;
 
         for _ in 0..dynamic_actor_count {
             let dynamic_actor = DynamicActor::read(reader)?;
-            let actor = actors.get_mut(&dynamic_actor.unique_id).unwrap();
+            let actor = actors
+                .get_mut(&dynamic_actor.unique_id)
+                .ok_or_else(|| anyhow!("Dynamic actor {} has no actor info", dynamic_actor.unique_id))?;
 
             actor.dynamic_data = Some(dynamic_actor);
         }
@@ -184,26 +273,27 @@ impl PersistenceContainer {
 }
 
 impl Timespan {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<Timespan> {
-        let value = reader.read_u64::<LittleEndian>()?;
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Timespan> {
+        let value = reader.read_u64::<Endian>()?;
 
         Ok(Timespan { value })
     }
 }
 
 impl DateTime {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<DateTime> {
-        let value = reader.read_u64::<LittleEndian>()?;
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<DateTime> {
+        let value = reader.read_u64::<Endian>()?;
 
         Ok(DateTime { value })
     }
 }
 
 impl DynamicStruct {
-    pub fn read(
-        reader: &mut Reader,
+    pub fn read<R: ArchiveReader>(
+        reader: &mut R,
         save_archive: &SaveGameArchiveContent,
     ) -> anyhow::Result<DynamicStruct> {
+        // no trailing u64 here, see DynamicStructComponent::write
         let properties = Property::read_multiple(reader, save_archive)?;
 
         Ok(DynamicStruct { properties })
@@ -211,9 +301,9 @@ impl DynamicStruct {
 }
 
 impl FPackageVersion {
-    pub fn read(reader: &mut Reader) -> anyhow::Result<Self> {
-        let ue4_version = reader.read_u32::<LittleEndian>()?;
-        let ue5_version = reader.read_u32::<LittleEndian>()?;
+    pub fn read<R: ArchiveReader>(reader: &mut R) -> anyhow::Result<Self> {
+        let ue4_version = reader.read_u32::<Endian>()?;
+        let ue5_version = reader.read_u32::<Endian>()?;
 
         Ok(FPackageVersion {
             ue4_version,
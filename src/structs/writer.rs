@@ -1,33 +1,102 @@
-use std::io::{Seek, SeekFrom, Write};
-use crate::io::{Writer, WriterExt};
-use crate::structs::{Actor, DateTime, DynamicActor, DynamicStruct, FGuid, FInfo, FPackageVersion, FQuaternion, FTopLevelAssetPath, FTransform, FVector, PersistenceBlob, PersistenceContainer, Timespan};
-use byteorder::{LittleEndian, WriteBytesExt};
+use std::io::SeekFrom;
+use crate::io::{ArchiveWriter, Endian, ObjectPadding};
+use crate::structs::{Actor, DateTime, DynamicActor, DynamicStruct, FBox, FBox2D, FGuid, FInfo, FIntVector, FLinearColor, FPackageVersion, FQuaternion, FRotator, FSphere, FTopLevelAssetPath, FTransform, FVector, FVector2D, PersistenceBlob, PersistenceContainer, Timespan};
+use byteorder::WriteBytesExt;
 use crate::properties::Property;
 use crate::sav::NameTable;
 
 impl FVector {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
-        writer.write_f64::<LittleEndian>(self.x)?;
-        writer.write_f64::<LittleEndian>(self.y)?;
-        writer.write_f64::<LittleEndian>(self.z)?;
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_f64::<Endian>(self.x)?;
+        writer.write_f64::<Endian>(self.y)?;
+        writer.write_f64::<Endian>(self.z)?;
 
         Ok(())
     }
 }
 
 impl FQuaternion {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
-        writer.write_f64::<LittleEndian>(self.w)?;
-        writer.write_f64::<LittleEndian>(self.x)?;
-        writer.write_f64::<LittleEndian>(self.y)?;
-        writer.write_f64::<LittleEndian>(self.z)?;
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_f64::<Endian>(self.w)?;
+        writer.write_f64::<Endian>(self.x)?;
+        writer.write_f64::<Endian>(self.y)?;
+        writer.write_f64::<Endian>(self.z)?;
+
+        Ok(())
+    }
+}
+
+impl FRotator {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_f64::<Endian>(self.pitch)?;
+        writer.write_f64::<Endian>(self.yaw)?;
+        writer.write_f64::<Endian>(self.roll)?;
+
+        Ok(())
+    }
+}
+
+impl FIntVector {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_i32::<Endian>(self.x)?;
+        writer.write_i32::<Endian>(self.y)?;
+        writer.write_i32::<Endian>(self.z)?;
+
+        Ok(())
+    }
+}
+
+impl FLinearColor {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_f32::<Endian>(self.r)?;
+        writer.write_f32::<Endian>(self.g)?;
+        writer.write_f32::<Endian>(self.b)?;
+        writer.write_f32::<Endian>(self.a)?;
+
+        Ok(())
+    }
+}
+
+impl FVector2D {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_f64::<Endian>(self.x)?;
+        writer.write_f64::<Endian>(self.y)?;
+
+        Ok(())
+    }
+}
+
+impl FBox {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.min.write(writer)?;
+        self.max.write(writer)?;
+        writer.write_u8(self.is_valid)?;
+
+        Ok(())
+    }
+}
+
+impl FBox2D {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.min.write(writer)?;
+        self.max.write(writer)?;
+        writer.write_u32::<Endian>(self.is_valid)?;
+
+        Ok(())
+    }
+}
+
+impl FSphere {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.center.write(writer)?;
+        writer.write_f64::<Endian>(self.w)?;
 
         Ok(())
     }
 }
 
 impl FTransform {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
         self.rotation.write(writer)?;
         self.position.write(writer)?;
         self.scale.write(writer)?;
@@ -37,18 +106,18 @@ impl FTransform {
 }
 
 impl FGuid {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
-        writer.write_u32::<LittleEndian>(self.a)?;
-        writer.write_u32::<LittleEndian>(self.b)?;
-        writer.write_u32::<LittleEndian>(self.c)?;
-        writer.write_u32::<LittleEndian>(self.d)?;
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u32::<Endian>(self.a)?;
+        writer.write_u32::<Endian>(self.b)?;
+        writer.write_u32::<Endian>(self.c)?;
+        writer.write_u32::<Endian>(self.d)?;
 
         Ok(())
     }
 }
 
 impl FTopLevelAssetPath {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
         writer.write_fstring(self.path.clone())?;
         writer.write_fstring(self.name.clone())?;
 
@@ -57,18 +126,18 @@ impl FTopLevelAssetPath {
 }
 
 impl FInfo {
-    fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
-        writer.write_u64::<LittleEndian>(self.unique_id)?;
-        writer.write_u32::<LittleEndian>(self.offset)?;
-        writer.write_u32::<LittleEndian>(self.size)?;
+    fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u64::<Endian>(self.unique_id)?;
+        writer.write_u32::<Endian>(self.offset)?;
+        writer.write_u32::<Endian>(self.size)?;
 
         Ok(())
     }
 }
 
 impl DynamicActor {
-    pub fn write(&self, writer: &mut Writer, unique_id: u64) -> anyhow::Result<()> {
-        writer.write_u64::<LittleEndian>(unique_id)?;
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W, unique_id: u64) -> anyhow::Result<()> {
+        writer.write_u64::<Endian>(unique_id)?;
         self.transform.write(writer)?;
         self.class_path.write(writer)?;
 
@@ -77,14 +146,14 @@ impl DynamicActor {
 }
 
 impl Actor {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
         match &self.transform {
             Some(transform) => {
-                writer.write_u32::<LittleEndian>(1)?;
+                writer.write_u32::<Endian>(1)?;
                 transform.write(writer)?;
             }
             None => {
-                writer.write_u32::<LittleEndian>(0)?;
+                writer.write_u32::<Endian>(0)?;
             }
         }
 
@@ -95,7 +164,7 @@ impl Actor {
 }
 
 impl PersistenceBlob {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
         self.archive.write(writer)?;
 
         Ok(())
@@ -103,21 +172,27 @@ impl PersistenceBlob {
 }
 
 impl PersistenceContainer {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
-        writer.write_u32::<LittleEndian>(self.version)?;
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        PersistenceContainer::check_version(self.version)?;
+
+        writer.write_u32::<Endian>(self.version)?;
 
         let index_offset = writer.position();
-        writer.write_u32::<LittleEndian>(0)?; // placeholder for index offset
+        writer.write_u32::<Endian>(0)?; // placeholder for index offset
 
         let dynamic_actors_offset = writer.position();
-        writer.write_u32::<LittleEndian>(0)?; // placeholder for dynamic actors offset
+        writer.write_u32::<Endian>(0)?; // placeholder for dynamic actors offset
 
         let mut actor_info = Vec::new();
 
-        for (unique_id, (_, actor)) in self.actors.iter().enumerate() {
+        // the original order is lost in the map, so actors are written sorted by id to keep output stable
+        let mut actors = self.actors.iter().collect::<Vec<_>>();
+
+        actors.sort_by_key(|(unique_id, _)| **unique_id);
+
+        for (&unique_id, actor) in actors {
             let offset = writer.position() as u32;
-            let buf = Vec::new();
-            let mut sub_writer = Writer::new(buf, 8);
+            let mut sub_writer = writer.sub_writer(ObjectPadding::Eight);
 
             actor.write(&mut sub_writer)?;
 
@@ -125,7 +200,7 @@ impl PersistenceContainer {
 
             let end_offset = writer.position() as u32;
             let info = FInfo {
-                unique_id: unique_id as u64,
+                unique_id,
                 offset,
                 size: end_offset - offset,
             };
@@ -136,30 +211,30 @@ impl PersistenceContainer {
         let index_offset_start = writer.position();
 
         writer.seek(SeekFrom::Start(index_offset))?;
-        writer.write_u32::<LittleEndian>(index_offset_start as u32)?;
+        writer.write_u32::<Endian>(index_offset_start as u32)?;
         writer.seek(SeekFrom::Start(index_offset_start))?;
 
-        writer.write_u32::<LittleEndian>(actor_info.len() as u32)?;
+        writer.write_u32::<Endian>(actor_info.len() as u32)?;
 
         for (info, _) in &actor_info {
             info.write(writer)?;
         }
 
-        writer.write_u32::<LittleEndian>(self.destroyed.len() as u32)?;
+        writer.write_u32::<Endian>(self.destroyed.len() as u32)?;
 
         for destroyed in &self.destroyed {
-            writer.write_u64::<LittleEndian>(*destroyed)?;
+            writer.write_u64::<Endian>(*destroyed)?;
         }
 
         let dynamic_actors_offset_start = writer.position();
 
         writer.seek(SeekFrom::Start(dynamic_actors_offset))?;
-        writer.write_u32::<LittleEndian>(dynamic_actors_offset_start as u32)?;
+        writer.write_u32::<Endian>(dynamic_actors_offset_start as u32)?;
         writer.seek(SeekFrom::Start(dynamic_actors_offset_start))?;
 
         let dynamic_actor_len_offset = writer.position();
         let mut dynamic_actor_len = 0;
-        writer.write_u32::<LittleEndian>(0)?; // placeholder for dynamic actor length
+        writer.write_u32::<Endian>(0)?; // placeholder for dynamic actor length
 
         for (info, actor) in actor_info {
             if let Some(dynamic_actor) = &actor.dynamic_data {
@@ -169,34 +244,35 @@ impl PersistenceContainer {
         }
 
         writer.seek(SeekFrom::Start(dynamic_actor_len_offset))?;
-        writer.write_u32::<LittleEndian>(dynamic_actor_len)?;
+        writer.write_u32::<Endian>(dynamic_actor_len)?;
 
         Ok(())
     }
 }
 
 impl Timespan {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
-        writer.write_u64::<LittleEndian>(self.value)?;
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u64::<Endian>(self.value)?;
 
         Ok(())
     }
 }
 
 impl DateTime {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
-        writer.write_u64::<LittleEndian>(self.value)?;
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u64::<Endian>(self.value)?;
 
         Ok(())
     }
 }
 
 impl DynamicStruct {
-    pub fn write(&self, writer: &mut Writer, name_table: &mut NameTable) -> anyhow::Result<()> {
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W, name_table: &mut NameTable) -> anyhow::Result<()> {
         for property in &self.properties {
             property.write(writer, name_table)?;
         }
 
+        // unlike DynamicStructComponent, struct values end at the None terminator
         Property::write_none(writer, name_table)?;
 
         Ok(())
@@ -204,9 +280,9 @@ impl DynamicStruct {
 }
 
 impl FPackageVersion {
-    pub fn write(&self, writer: &mut Writer) -> anyhow::Result<()> {
-        writer.write_u32::<LittleEndian>(self.ue4_version)?;
-        writer.write_u32::<LittleEndian>(self.ue5_version)?;
+    pub fn write<W: ArchiveWriter>(&self, writer: &mut W) -> anyhow::Result<()> {
+        writer.write_u32::<Endian>(self.ue4_version)?;
+        writer.write_u32::<Endian>(self.ue5_version)?;
 
         Ok(())
     }
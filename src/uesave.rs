@@ -0,0 +1,522 @@
+// Conversion between the native JSON layout and the property layout used by uesave-rs.
+//
+// Only the property lists of objects and dynamic struct components are converted, everything
+// else (header, name table, object index) keeps the native layout since uesave has no equivalent.
+// Values that don't map cleanly onto uesave (text, struct references, persistence blobs and
+// containers, raw fallback bytes) are embedded in their native form under a `Remnant` key.
+//
+// Differences to uesave worth knowing about:
+// - guids use the UE `XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX` format
+// - object properties hold the index into the object table, not a path
+// - `Base` arrays hold a list of tagged values instead of a typed vector
+// - property sizes are not exported, they are recomputed on write
+// - properties with a non-zero index (static arrays) are keyed as `Name[index]`
+// - numbered names are keyed as `Name_number`, the number is kept in `name_number` as well
+
+use std::str::FromStr;
+use anyhow::{bail, Context};
+use serde_json::{json, Map, Value};
+use crate::properties::{ArrayProperty, ByteProperty, BytePropertyValue, EnumProperty, HeadData, MapProperty, Property, PropertyData, StructProperty};
+use crate::sav::SaveGameArchive;
+use crate::structs::{DateTime, DynamicStruct, FGuid, FIntVector, FLinearColor, FName, FQuaternion, FRotator, FVector, StructData, Timespan};
+
+const REMNANT_TAG: &str = "Remnant";
+
+// uesave tag -> property type name
+const PROPERTY_TAGS: [(&str, &str); 18] = [
+    ("Bool", "BoolProperty"),
+    ("Byte", "ByteProperty"),
+    ("Enum", "EnumProperty"),
+    ("Int16", "Int16Property"),
+    ("Int", "IntProperty"),
+    ("Int64", "Int64Property"),
+    ("UInt16", "UInt16Property"),
+    ("UInt32", "UInt32Property"),
+    ("UInt64", "UInt64Property"),
+    ("Float", "FloatProperty"),
+    ("Double", "DoubleProperty"),
+    ("Str", "StrProperty"),
+    ("Name", "NameProperty"),
+    ("Object", "ObjectProperty"),
+    ("SoftObject", "SoftObjectProperty"),
+    ("Struct", "StructProperty"),
+    ("Array", "ArrayProperty"),
+    ("Map", "MapProperty"),
+];
+
+pub fn export(archive: &SaveGameArchive) -> anyhow::Result<Value> {
+    let mut value = serde_json::to_value(archive)?;
+
+    convert_properties(&mut value, |properties| {
+        let properties: Vec<Property> = serde_json::from_value(properties.take())?;
+
+        Ok(export_properties(&properties))
+    })?;
+
+    Ok(value)
+}
+
+pub fn import(mut value: Value) -> anyhow::Result<SaveGameArchive> {
+    convert_properties(&mut value, |properties| {
+        let properties = import_properties(properties)?;
+
+        Ok(serde_json::to_value(properties)?)
+    })?;
+
+    Ok(serde_json::from_value(value)?)
+}
+
+fn convert_properties(
+    value: &mut Value,
+    mut convert: impl FnMut(&mut Value) -> anyhow::Result<Value>,
+) -> anyhow::Result<()> {
+    let objects = value.pointer_mut("/content/object_index")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| anyhow::anyhow!("Archive has no object index"))?;
+
+    for object in objects {
+        if let Some(properties) = object.get_mut("properties") {
+            *properties = convert(properties)?;
+        }
+
+        if let Some(components) = object.get_mut("components").and_then(Value::as_array_mut) {
+            for component in components {
+                if let Some(properties) = component.pointer_mut("/component_type/DynamicStruct/properties") {
+                    *properties = convert(properties)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// export
+
+pub fn export_properties(properties: &[Property]) -> Value {
+    let mut map = Map::new();
+
+    for property in properties {
+        let mut value = export_property(property);
+
+        if let Some(inner) = value.as_object_mut()
+            .and_then(|tagged| tagged.values_mut().next())
+            .and_then(Value::as_object_mut)
+        {
+            if property.name.number.is_some() {
+                inner.insert("name_number".to_owned(), json!(property.name.number));
+            }
+        }
+
+        // the number is part of the key, or names that only differ by it would overwrite each other
+        let key = if property.index != 0 {
+            format!("{}[{}]", property.name.to_display_string(), property.index)
+        } else {
+            property.name.to_display_string()
+        };
+
+        map.insert(key, value);
+    }
+
+    Value::Object(map)
+}
+
+fn export_property(property: &Property) -> Value {
+    let value = match &property.data {
+        PropertyData::Bool(value) => json!(value),
+        PropertyData::Int16(value) => json!(value),
+        PropertyData::Int32(value) => json!(value),
+        PropertyData::Int64(value) => json!(value),
+        PropertyData::UInt16(value) => json!(value),
+        PropertyData::UInt32(value) => json!(value),
+        PropertyData::UInt64(value) => json!(value),
+        PropertyData::Float(value) => json!(value),
+        PropertyData::Double(value) => json!(value),
+        PropertyData::Str(value) => json!(value),
+        PropertyData::SoftObject(value) => json!(value),
+        PropertyData::Object(value) => json!(value),
+        PropertyData::Name(value) => export_name(value),
+        PropertyData::Byte(value) => {
+            return json!({ "Byte": {
+                "id": null,
+                "enum_type": export_name(&value.enum_name),
+                "value": match &value.value {
+                    BytePropertyValue::Byte(byte) => json!({ "Byte": byte }),
+                    BytePropertyValue::Enum(label) => json!({ "Label": export_name(label) }),
+                },
+            }});
+        }
+        PropertyData::Enum(value) => {
+            return json!({ "Enum": {
+                "id": null,
+                "enum_type": export_name(&value.enum_name),
+                "value": export_name(&value.value),
+            }});
+        }
+        PropertyData::Struct(value) => {
+            return json!({ "Struct": {
+                "id": null,
+                "struct_type": export_name(&value.struct_name),
+                "struct_id": value.guid.to_string(),
+                "value": export_struct(&value.data),
+            }});
+        }
+        PropertyData::Array(value) => {
+            return json!({ "Array": {
+                "array_type": export_name(&value.inner_type),
+                "id": null,
+                "value": export_array(value),
+            }});
+        }
+        PropertyData::Map(value) => {
+            let elements = value.elements.iter()
+                .map(|(key, value)| json!({ "key": export_value(key), "value": export_value(value) }))
+                .collect::<Vec<_>>();
+
+            return json!({ "Map": {
+                "key_type": export_name(&value.key_type),
+                "value_type": export_name(&value.value_type),
+                "id": null,
+                "value": elements,
+            }});
+        }
+        PropertyData::Text(_) | PropertyData::StructReference(_) | PropertyData::Raw(_) => {
+            return json!({ REMNANT_TAG: {
+                "type_name": export_name(&property.type_name),
+                "size": property.size,
+                "value": native(&property.data),
+            }});
+        }
+    };
+
+    json!({ property_tag(&property.data): { "id": null, "value": value } })
+}
+
+fn export_array(array: &ArrayProperty) -> Value {
+    match &array.head_data {
+        HeadData::Struct { name, type_name, index, struct_name, guid } => {
+            let elements = array.elements.iter()
+                .map(|element| match element {
+                    PropertyData::Struct(element) => export_struct(&element.data),
+                    _ => export_value(element),
+                })
+                .collect::<Vec<_>>();
+
+            json!({ "Struct": {
+                "_type": export_name(type_name),
+                "name": export_name(name),
+                "struct_type": export_name(struct_name),
+                "id": guid.to_string(),
+                "index": index,
+                "value": elements,
+            }})
+        }
+        HeadData::None => {
+            json!({ "Base": array.elements.iter().map(export_value).collect::<Vec<_>>() })
+        }
+    }
+}
+
+fn export_value(data: &PropertyData) -> Value {
+    match data {
+        PropertyData::Bool(value) => json!({ "Bool": value }),
+        PropertyData::Int16(value) => json!({ "Int16": value }),
+        PropertyData::Int32(value) => json!({ "Int": value }),
+        PropertyData::Int64(value) => json!({ "Int64": value }),
+        PropertyData::UInt16(value) => json!({ "UInt16": value }),
+        PropertyData::UInt32(value) => json!({ "UInt32": value }),
+        PropertyData::UInt64(value) => json!({ "UInt64": value }),
+        PropertyData::Float(value) => json!({ "Float": value }),
+        PropertyData::Double(value) => json!({ "Double": value }),
+        PropertyData::Str(value) => json!({ "Str": value }),
+        PropertyData::SoftObject(value) => json!({ "SoftObject": value }),
+        PropertyData::Object(value) => json!({ "Object": value }),
+        PropertyData::Name(value) => json!({ "Name": export_name(value) }),
+        PropertyData::Byte(ByteProperty { value: BytePropertyValue::Byte(value), .. }) => {
+            json!({ "Byte": value })
+        }
+        PropertyData::Struct(value) => json!({ "Struct": export_struct(&value.data) }),
+        _ => json!({ REMNANT_TAG: native(data) }),
+    }
+}
+
+fn export_struct(data: &StructData) -> Value {
+    match data {
+        StructData::Guid(guid) => json!({ "Guid": guid.to_string() }),
+        StructData::DateTime(date_time) => json!({ "DateTime": date_time.value }),
+        StructData::Timespan(timespan) => json!({ "Timespan": timespan.value }),
+        StructData::Vector(vector) => json!({ "Vector": vector }),
+        StructData::Rotator(rotator) => json!({ "Rotator": rotator }),
+        StructData::Quat(quat) => json!({ "Quat": quat }),
+        StructData::IntVector(vector) => json!({ "IntVector": vector }),
+        StructData::LinearColor(color) => json!({ "LinearColor": color }),
+        StructData::SoftObjectPath(path) => json!({ "SoftObjectPath": path }),
+        StructData::SoftClassPath(path) => json!({ "SoftClassPath": path }),
+        StructData::Dynamic(dynamic_struct) => json!({ "Struct": export_properties(&dynamic_struct.properties) }),
+        _ => json!({ REMNANT_TAG: native(data) }),
+    }
+}
+
+fn export_name(name: &FName) -> Value {
+    match name.number {
+        None => json!(name.value),
+        Some(_) => native(name),
+    }
+}
+
+fn native<T: serde::Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("native types always serialize")
+}
+
+fn property_tag(data: &PropertyData) -> &'static str {
+    match data {
+        PropertyData::Bool(_) => "Bool",
+        PropertyData::Byte(_) => "Byte",
+        PropertyData::Enum(_) => "Enum",
+        PropertyData::Int16(_) => "Int16",
+        PropertyData::Int32(_) => "Int",
+        PropertyData::Int64(_) => "Int64",
+        PropertyData::UInt16(_) => "UInt16",
+        PropertyData::UInt32(_) => "UInt32",
+        PropertyData::UInt64(_) => "UInt64",
+        PropertyData::Float(_) => "Float",
+        PropertyData::Double(_) => "Double",
+        PropertyData::Str(_) => "Str",
+        PropertyData::Name(_) => "Name",
+        PropertyData::Object(_) => "Object",
+        PropertyData::SoftObject(_) => "SoftObject",
+        PropertyData::Struct(_) => "Struct",
+        PropertyData::Array(_) => "Array",
+        PropertyData::Map(_) => "Map",
+        PropertyData::Text(_) | PropertyData::StructReference(_) | PropertyData::Raw(_) => REMNANT_TAG,
+    }
+}
+
+// import
+
+pub fn import_properties(value: &Value) -> anyhow::Result<Vec<Property>> {
+    let map = value.as_object()
+        .ok_or_else(|| anyhow::anyhow!("Expected a property map, got {}", value))?;
+
+    map.iter()
+        .map(|(name, value)| import_property(name, value).with_context(|| format!("Property {}", name)))
+        .collect()
+}
+
+fn import_property(key: &str, value: &Value) -> anyhow::Result<Property> {
+    let (tag, inner) = tagged(value)?;
+
+    let (name, index) = match key.strip_suffix(']').and_then(|key| key.rsplit_once('[')) {
+        Some((name, index)) => (name, index.parse::<u32>()?),
+        None => (key, 0),
+    };
+    let number = inner.get("name_number").and_then(Value::as_u64).map(|number| number as u32);
+    let name = match number {
+        Some(number) => name.strip_suffix(&format!("_{}", number))
+            .ok_or_else(|| anyhow::anyhow!("Key {} doesn't end with its name number {}", key, number))?,
+        None => name,
+    };
+    let name = FName { value: name.to_owned(), number };
+
+    if tag == REMNANT_TAG {
+        return Ok(Property {
+            name,
+            index,
+            type_name: import_name(field(inner, "type_name")?)?,
+            size: field(inner, "size")?.as_u64().unwrap_or(0) as u32,
+            data: serde_json::from_value(field(inner, "value")?.clone())?,
+            offset: None,
+        });
+    }
+
+    let type_name = PROPERTY_TAGS.iter()
+        .find(|(t, _)| *t == tag)
+        .map(|(_, type_name)| FName::from(type_name))
+        .ok_or_else(|| anyhow::anyhow!("Unknown property tag: {}", tag))?;
+
+    let data = match tag {
+        "Byte" => {
+            let (kind, value) = tagged(field(inner, "value")?)?;
+
+            PropertyData::Byte(ByteProperty {
+                enum_name: import_name(field(inner, "enum_type")?)?,
+                value: match kind {
+                    "Byte" => BytePropertyValue::Byte(as_u64(value)? as u8),
+                    _ => BytePropertyValue::Enum(import_name(value)?),
+                },
+            })
+        }
+        "Enum" => PropertyData::Enum(EnumProperty {
+            enum_name: import_name(field(inner, "enum_type")?)?,
+            value: import_name(field(inner, "value")?)?,
+        }),
+        "Struct" => PropertyData::Struct(StructProperty {
+            struct_name: import_name(field(inner, "struct_type")?)?,
+            guid: import_guid(field(inner, "struct_id")?)?,
+            data: import_struct(field(inner, "value")?)?,
+        }),
+        "Array" => PropertyData::Array(import_array(inner)?),
+        "Map" => {
+            let elements = field(inner, "value")?.as_array()
+                .ok_or_else(|| anyhow::anyhow!("Map value is not a list"))?
+                .iter()
+                .map(|element| Ok((import_value(field(element, "key")?)?, import_value(field(element, "value")?)?)))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            PropertyData::Map(MapProperty {
+                key_type: import_name(field(inner, "key_type")?)?,
+                value_type: import_name(field(inner, "value_type")?)?,
+                elements,
+            })
+        }
+        _ => import_value(&json!({ tag: field(inner, "value")? }))?,
+    };
+
+    Ok(Property {
+        name,
+        index,
+        type_name,
+        size: 0,
+        data,
+        offset: None,
+    })
+}
+
+fn import_array(inner: &Value) -> anyhow::Result<ArrayProperty> {
+    let inner_type = import_name(field(inner, "array_type")?)?;
+    let (kind, value) = tagged(field(inner, "value")?)?;
+
+    let (head_data, elements) = match kind {
+        "Struct" => {
+            let struct_name = import_name(field(value, "struct_type")?)?;
+            let guid = import_guid(field(value, "id")?)?;
+
+            let elements = list(field(value, "value")?)?
+                .iter()
+                .map(|element| Ok(PropertyData::Struct(StructProperty {
+                    struct_name: struct_name.clone(),
+                    guid: guid.clone(),
+                    data: import_struct(element)?,
+                })))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let head_data = HeadData::Struct {
+                name: import_name(field(value, "name")?)?,
+                type_name: import_name(field(value, "_type")?)?,
+                index: value.get("index").and_then(Value::as_u64).unwrap_or(0) as u32,
+                struct_name,
+                guid,
+            };
+
+            (head_data, elements)
+        }
+        "Base" => {
+            let elements = list(value)?
+                .iter()
+                .map(import_value)
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            (HeadData::None, elements)
+        }
+        _ => bail!("Unknown array kind: {}", kind),
+    };
+
+    Ok(ArrayProperty {
+        inner_type,
+        head_data,
+        elements,
+    })
+}
+
+fn import_value(value: &Value) -> anyhow::Result<PropertyData> {
+    let (tag, value) = tagged(value)?;
+
+    let data = match tag {
+        "Bool" => PropertyData::Bool(value.as_bool().ok_or_else(|| anyhow::anyhow!("Expected a bool"))?),
+        "Int16" => PropertyData::Int16(serde_json::from_value(value.clone())?),
+        "Int" => PropertyData::Int32(serde_json::from_value(value.clone())?),
+        "Int64" => PropertyData::Int64(serde_json::from_value(value.clone())?),
+        "UInt16" => PropertyData::UInt16(serde_json::from_value(value.clone())?),
+        "UInt32" => PropertyData::UInt32(serde_json::from_value(value.clone())?),
+        "UInt64" => PropertyData::UInt64(serde_json::from_value(value.clone())?),
+        "Float" => PropertyData::Float(serde_json::from_value(value.clone())?),
+        "Double" => PropertyData::Double(serde_json::from_value(value.clone())?),
+        "Str" => PropertyData::Str(serde_json::from_value(value.clone())?),
+        "SoftObject" => PropertyData::SoftObject(serde_json::from_value(value.clone())?),
+        "Object" => PropertyData::Object(serde_json::from_value(value.clone())?),
+        "Name" => PropertyData::Name(import_name(value)?),
+        "Byte" => PropertyData::Byte(ByteProperty {
+            enum_name: FName::none(),
+            value: BytePropertyValue::Byte(as_u64(value)? as u8),
+        }),
+        "Struct" => PropertyData::Struct(StructProperty {
+            struct_name: FName::none(),
+            guid: FGuid::default(),
+            data: import_struct(value)?,
+        }),
+        REMNANT_TAG => serde_json::from_value(value.clone())?,
+        _ => bail!("Unknown value tag: {}", tag),
+    };
+
+    Ok(data)
+}
+
+fn import_struct(value: &Value) -> anyhow::Result<StructData> {
+    let (tag, value) = tagged(value)?;
+
+    let data = match tag {
+        "Guid" => StructData::Guid(import_guid(value)?),
+        "DateTime" => StructData::DateTime(DateTime { value: as_u64(value)? }),
+        "Timespan" => StructData::Timespan(Timespan { value: as_u64(value)? }),
+        "Vector" => StructData::Vector(serde_json::from_value::<FVector>(value.clone())?),
+        "Rotator" => StructData::Rotator(serde_json::from_value::<FRotator>(value.clone())?),
+        "Quat" => StructData::Quat(serde_json::from_value::<FQuaternion>(value.clone())?),
+        "IntVector" => StructData::IntVector(serde_json::from_value::<FIntVector>(value.clone())?),
+        "LinearColor" => StructData::LinearColor(serde_json::from_value::<FLinearColor>(value.clone())?),
+        "SoftObjectPath" => StructData::SoftObjectPath(serde_json::from_value(value.clone())?),
+        "SoftClassPath" => StructData::SoftClassPath(serde_json::from_value(value.clone())?),
+        "Struct" => StructData::Dynamic(DynamicStruct { properties: import_properties(value)? }),
+        REMNANT_TAG => serde_json::from_value(value.clone())?,
+        _ => bail!("Unknown struct tag: {}", tag),
+    };
+
+    Ok(data)
+}
+
+fn import_name(value: &Value) -> anyhow::Result<FName> {
+    match value {
+        Value::String(name) => Ok(FName::from(name)),
+        _ => Ok(serde_json::from_value(value.clone())?),
+    }
+}
+
+fn import_guid(value: &Value) -> anyhow::Result<FGuid> {
+    match value {
+        Value::Null => Ok(FGuid::default()),
+        Value::String(guid) => FGuid::from_str(guid),
+        _ => bail!("Expected a guid string, got {}", value),
+    }
+}
+
+fn tagged(value: &Value) -> anyhow::Result<(&str, &Value)> {
+    match value.as_object() {
+        Some(map) if map.len() == 1 => {
+            let (tag, value) = map.iter().next().expect("map has one entry");
+
+            Ok((tag.as_str(), value))
+        }
+        _ => bail!("Expected a single tagged value, got {}", value),
+    }
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> anyhow::Result<&'a Value> {
+    value.get(name).ok_or_else(|| anyhow::anyhow!("Missing field {} in {}", name, value))
+}
+
+fn list(value: &Value) -> anyhow::Result<&Vec<Value>> {
+    value.as_array().ok_or_else(|| anyhow::anyhow!("Expected a list, got {}", value))
+}
+
+fn as_u64(value: &Value) -> anyhow::Result<u64> {
+    value.as_u64().ok_or_else(|| anyhow::anyhow!("Expected an unsigned integer, got {}", value))
+}
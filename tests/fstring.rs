@@ -0,0 +1,57 @@
+use remnant_save_parser::io::{ReaderExt, WriterExt};
+use remnant_save_parser::{ObjectPadding, Reader, Writer};
+
+fn round_trip(value: &str) -> Vec<u8> {
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+    writer.write_fstring(value.to_owned()).expect("write fstring");
+
+    let bytes = writer.into_inner();
+    let read = Reader::new(bytes.clone(), ObjectPadding::Four).read_fstring().expect("read fstring");
+
+    assert_eq!(read, value);
+
+    bytes
+}
+
+#[test]
+fn ascii_is_narrow() {
+    assert_eq!(round_trip("Archon"), b"\x07\x00\x00\x00Archon\x00");
+}
+
+#[test]
+fn cyrillic_is_utf16() {
+    let bytes = round_trip("Странник");
+
+    assert_eq!(&bytes[..4], &(-9i32).to_le_bytes());
+    assert_eq!(bytes.len(), 4 + 9 * 2);
+}
+
+#[test]
+fn cjk_is_utf16() {
+    let bytes = round_trip("旅人");
+
+    assert_eq!(&bytes[..4], &(-3i32).to_le_bytes());
+    assert_eq!(&bytes[4..], &[0xC5, 0x65, 0xBA, 0x4E, 0x00, 0x00]);
+}
+
+#[test]
+fn narrow_is_latin1() {
+    let bytes = b"\x05\x00\x00\x00caf\xE9\x00";
+    let read = Reader::new(bytes.to_vec(), ObjectPadding::Four).read_fstring().expect("read fstring");
+
+    assert_eq!(read, "café");
+    assert_eq!(round_trip(&read), bytes);
+}
+
+#[test]
+fn lengths_past_the_end_are_rejected() {
+    for length in [i32::MAX, i32::MIN + 1] {
+        let mut bytes = length.to_le_bytes().to_vec();
+        bytes.extend_from_slice(b"short\x00");
+
+        let err = Reader::new(bytes, ObjectPadding::Four).read_fstring().unwrap_err();
+
+        assert!(err.to_string().contains("past the end of the data"), "{}", err);
+    }
+}
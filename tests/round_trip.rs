@@ -0,0 +1,2498 @@
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use flate2::bufread::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
+use std::path::Path;
+use remnant_save_parser::{Compressor, DynamicStruct, FGuid, ObjectPadding, Property, PropertyData, Reader, SavFile, SaveGameArchive, SaveVersion, StructData, Writer};
+use remnant_save_parser::components::{Component, ComponentType, DynamicStructComponent, Variable, Variables, VariableValue};
+use remnant_save_parser::inventory::{Inventory, InventoryItem};
+use remnant_save_parser::io::Endian;
+use remnant_save_parser::loadout::Loadout;
+use remnant_save_parser::sav::NameTable;
+use remnant_save_parser::scrub::Scrubber;
+use remnant_save_parser::properties::{PersistenceBlobKind, TextProperty};
+use remnant_save_parser::uesave;
+use remnant_save_parser::structs::{FName, PersistenceContainer};
+use serde_json::{json, Value};
+
+const HEX_WINDOW: usize = 16;
+
+fn read_sav(bytes: Vec<u8>) -> SavFile {
+    SavFile::read(&mut Reader::new(bytes, ObjectPadding::Four)).expect("read sav file")
+}
+
+fn write_sav(archive: &SaveGameArchive, compressor: &Compressor) -> Vec<u8> {
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+    SavFile::write(&mut writer, archive, compressor).expect("write sav file");
+
+    writer.into_inner()
+}
+
+fn hex_window(bytes: &[u8], offset: usize) -> String {
+    let start = offset.saturating_sub(HEX_WINDOW);
+    let end = (offset + HEX_WINDOW).min(bytes.len());
+
+    bytes[start..end].iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// compares decompressed content, since the compression framing is allowed to differ
+fn assert_content_eq(name: &str, expected: &[u8], actual: &[u8]) {
+    if expected == actual {
+        return;
+    }
+
+    let offset = expected.iter()
+        .zip(actual)
+        .position(|(a, b)| a != b)
+        .unwrap_or(expected.len().min(actual.len()));
+
+    panic!(
+        "{}: content differs at offset {:#X} (expected {} bytes, got {})\nexpected: {}\nactual:   {}",
+        name,
+        offset,
+        expected.len(),
+        actual.len(),
+        hex_window(expected, offset),
+        hex_window(actual, offset),
+    );
+}
+
+fn round_trip(name: &str, bytes: Vec<u8>) {
+    let sav_file = read_sav(bytes);
+    let archive = sav_file.get_archive().expect("read archive");
+
+    let bytes = write_sav(&archive, sav_file.compressor());
+
+    SavFile::verify(&bytes).unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+    let written = read_sav(bytes);
+
+    assert_content_eq(
+        name,
+        &sav_file.get_content().expect("source content"),
+        &written.get_content().expect("written content"),
+    );
+}
+
+fn property(name: &str, type_name: &str, data: Value) -> Value {
+    json!({
+        "name": { "value": name },
+        "index": 0,
+        "type_name": { "value": type_name },
+        "size": 0,
+        "data": data
+    })
+}
+
+fn sample_archive() -> SaveGameArchive {
+    let class_path = "/Game/_Core/Blueprints/Base/BP_RemnantSaveGameProfile";
+
+    let loadout_tags = json!({ "Array": {
+        "inner_type": { "value": "EnumProperty" },
+        "head_data": "None",
+        "elements": [
+            { "Enum": { "enum_name": { "value": "None" }, "value": { "value": "ELoadoutTag::Primary" } } },
+            { "Enum": { "enum_name": { "value": "None" }, "value": { "value": "ELoadoutTag::Melee" } } }
+        ]
+    } });
+
+    let persistence_keys = json!({ "Map": {
+        "key_type": { "value": "StructProperty" },
+        "value_type": { "value": "StructProperty" },
+        "elements": [[
+            { "StructReference": { "a": 1, "b": 2, "c": 3, "d": 4 } },
+            { "Struct": {
+                "struct_name": { "value": "None" },
+                "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+                "data": { "Dynamic": { "properties": [property("Count", "IntProperty", json!({ "Int32": 3 }))] } }
+            } }
+        ]]
+    } });
+
+    let quest_title = json!({ "Text": {
+        "flags": 0,
+        "data": { "ArgumentFormat": {
+            "source_format": { "flags": 0, "data": { "Base": {
+                "namespace": "Quests",
+                "key": "KillCount",
+                "source_string": "Killed {Count} of {Total}"
+            } } },
+            "arguments": [
+                ["Count", { "Int": 3 }],
+                ["Total", { "Text": { "flags": 0, "data": { "None": { "culture_invariant_string": "десять" } } } }]
+            ]
+        } }
+    } });
+
+    let properties = vec![
+        property("Level", "IntProperty", json!({ "Int32": 20 })),
+        property("CharacterName", "StrProperty", json!({ "Str": "Странник 旅人" })),
+        property("LoadoutTags", "ArrayProperty", loadout_tags),
+        property("PersistenceKeys", "MapProperty", persistence_keys),
+        property("QuestTitle", "TextProperty", quest_title),
+        property("GridCell", "StructProperty", json!({ "Struct": {
+            "struct_name": { "value": "IntVector" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+            "data": { "IntVector": { "x": 1, "y": -2, "z": 3 } }
+        } })),
+        property("DyeColor", "StructProperty", json!({ "Struct": {
+            "struct_name": { "value": "LinearColor" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+            "data": { "LinearColor": { "r": 1.0, "g": 0.5, "b": 0.25, "a": 1.0 } }
+        } })),
+        property("SpawnRotation", "StructProperty", json!({ "Struct": {
+            "struct_name": { "value": "Rotator" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+            "data": { "Rotator": { "pitch": -12.5, "yaw": 90.0, "roll": 0.25 } }
+        } })),
+    ];
+
+    serde_json::from_value(json!({
+        "header": { "save_game_file_version": 9, "build_number": 400000 },
+        "content": {
+            "package_version": { "ue4_version": 522, "ue5_version": 1008 },
+            "save_game_class_path": { "path": class_path, "name": "BP_RemnantSaveGameProfile_C" },
+            "name_table": { "list": [] },
+            "object_index": [{
+                "object_id": 0,
+                "was_loaded": true,
+                "object_path": class_path,
+                "loaded_data": null,
+                "properties": properties,
+                "trailing_data": [1, 2, 3, 4],
+                "components": null
+            }, {
+                "object_id": 1,
+                "was_loaded": false,
+                "object_path": "/Game/World_Base/Quests/Quest_Global",
+                "loaded_data": { "name": { "value": "Quest_Global_C", "number": 2 }, "outer_id": 0 },
+                "properties": [],
+                "components": null
+            }],
+            "version": 1
+        }
+    })).expect("sample archive")
+}
+
+const DROPPED_ITEM_CLASS: &str = "/Game/World_Base/Items/BP_DroppedItem";
+
+fn actor(unique_id: u64, class_path: Option<&str>) -> Value {
+    let transform = json!({
+        "rotation": { "w": 1.0, "x": 0.0, "y": 0.0, "z": 0.0 },
+        "position": { "x": unique_id as f64, "y": 2.0, "z": 3.0 },
+        "scale": { "x": 1.0, "y": 1.0, "z": 1.0 }
+    });
+
+    let dynamic_data = class_path.map(|path| json!({
+        "unique_id": unique_id,
+        "transform": transform,
+        "class_path": { "path": path, "name": "BP_DroppedItem_C" }
+    }));
+
+    json!({
+        "transform": transform,
+        "archive": {
+            "package_version": null,
+            "save_game_class_path": null,
+            "name_table": { "list": [] },
+            "object_index": [{
+                "object_id": 0,
+                "was_loaded": true,
+                "object_path": format!("/Game/World_Base/Actors/Actor_{}", unique_id),
+                "loaded_data": null,
+                "properties": [property("Quantity", "IntProperty", json!({ "Int32": unique_id }))],
+                "components": null
+            }],
+            "version": 1
+        },
+        "dynamic_data": dynamic_data
+    })
+}
+
+// a world save, its persistence container holds two dropped items and a placed actor
+fn world_archive() -> SaveGameArchive {
+    let class_path = "/Game/_Core/Blueprints/Base/BP_RemnantSaveGame";
+
+    let persistence = json!({ "Struct": {
+        "struct_name": { "value": "PersistenceBlob" },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { "PersistenceContainer": {
+            "version": 4,
+            "destroyed": [7],
+            "actors": {
+                "9": actor(9, Some(DROPPED_ITEM_CLASS)),
+                "2": actor(2, None),
+                "5": actor(5, Some(DROPPED_ITEM_CLASS))
+            }
+        } }
+    } });
+
+    serde_json::from_value(json!({
+        "header": { "save_game_file_version": 9, "build_number": 400000 },
+        "content": {
+            "package_version": { "ue4_version": 522, "ue5_version": 1008 },
+            "save_game_class_path": { "path": class_path, "name": "BP_RemnantSaveGame_C" },
+            "name_table": { "list": [] },
+            "object_index": [{
+                "object_id": 0,
+                "was_loaded": true,
+                "object_path": class_path,
+                "loaded_data": null,
+                "properties": [property("PersistenceData", "StructProperty", persistence)],
+                "components": null
+            }],
+            "version": 1
+        }
+    })).expect("world archive")
+}
+
+fn container(archive: &SaveGameArchive) -> &PersistenceContainer {
+    match &archive.root().unwrap().get_struct("PersistenceData").unwrap().data {
+        StructData::PersistenceContainer(container) => container,
+        data => panic!("expected a persistence container, got {:?}", data),
+    }
+}
+
+// a destroyed id is orphaned when the container holds no actor for it
+#[test]
+fn container_stats_report_orphaned_destroyed_ids() {
+    let mut world = serde_json::to_value(world_archive()).unwrap();
+    *world.pointer_mut("/content/object_index/0/properties/0/data/Struct/data/PersistenceContainer/destroyed").unwrap() = json!([5, 7]);
+
+    let archive: SaveGameArchive = serde_json::from_value(world).unwrap();
+    let read = read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap();
+    let stats = container(&read).stats();
+
+    assert_eq!(stats.actor_count, 3);
+    assert_eq!(stats.destroyed_count, 2);
+    assert_eq!(stats.orphaned_destroyed, [7]);
+}
+
+#[test]
+fn actors_by_class_finds_dropped_items() {
+    let archive = world_archive();
+
+    let mut unique_ids = container(&archive)
+        .actors_by_class(DROPPED_ITEM_CLASS)
+        .map(|(unique_id, _)| *unique_id)
+        .collect::<Vec<_>>();
+
+    unique_ids.sort();
+
+    assert_eq!(unique_ids, [5, 9]);
+    assert_eq!(container(&archive).actors_by_class("/Game/Missing").count(), 0);
+}
+
+// a reference resolves to the actor whose root object has a Guid struct with the same value
+#[test]
+fn struct_references_resolve_to_actors() {
+    let guid = json!({ "a": 5, "b": 6, "c": 7, "d": 8 });
+    let mut world = serde_json::to_value(world_archive()).unwrap();
+
+    let actor_properties = "/content/object_index/0/properties/0/data/Struct/data/PersistenceContainer/actors/5/archive/object_index/0/properties";
+    world.pointer_mut(actor_properties).unwrap().as_array_mut().unwrap().push(property("ActorGuid", "StructProperty", json!({ "Struct": {
+        "struct_name": { "value": "Guid" },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { "Guid": guid }
+    } })));
+
+    world.pointer_mut("/content/object_index/0/properties").unwrap().as_array_mut().unwrap().push(property("PersistenceKeys", "MapProperty", json!({ "Map": {
+        "key_type": { "value": "StructProperty" },
+        "value_type": { "value": "IntProperty" },
+        "elements": [[{ "StructReference": guid }, { "Int32": 1 }]]
+    } })));
+
+    let archive: SaveGameArchive = serde_json::from_value(world).unwrap();
+    let read = read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap();
+    let references = read.content.struct_references();
+
+    assert_eq!(references.len(), 1);
+
+    let (object_path, path, guid) = &references[0];
+    assert_eq!(*object_path, "/Game/_Core/Blueprints/Base/BP_RemnantSaveGame");
+    assert_eq!(*path, format!("PersistenceKeys[{}]", guid));
+
+    let actor = read.content.resolve_actor(guid).expect("resolve the referenced actor");
+    assert_eq!(actor.archive.object_index[0].object_path, "/Game/World_Base/Actors/Actor_5");
+
+    let unknown: FGuid = serde_json::from_value(json!({ "a": 1, "b": 0, "c": 0, "d": 0 })).unwrap();
+    assert!(read.content.resolve_actor(&unknown).is_none());
+}
+
+// actors have to keep their ids, and a HashMap must not change the written bytes
+#[test]
+fn world_archive_round_trips() {
+    let archive = world_archive();
+    let bytes = write_sav(&archive, &Compressor::Zlib);
+
+    assert_eq!(bytes, write_sav(&archive, &Compressor::Zlib));
+
+    let read = read_sav(bytes.clone()).get_archive().expect("read world archive");
+
+    assert!(read.semantic_eq(&archive).unwrap(), "world archive changed on write");
+
+    round_trip("world", bytes);
+}
+
+#[test]
+fn visit_properties_walks_into_containers() {
+    let mut archive = world_archive();
+    let mut visited = Vec::new();
+
+    archive.visit_properties(&mut |property| match property.data {
+        PropertyData::Int32(value) => visited.push(format!("{}={}", property.name.value, value)),
+        _ => visited.push(property.name.value.clone()),
+    });
+
+    assert_eq!(visited, ["PersistenceData", "Quantity=2", "Quantity=5", "Quantity=9"]);
+
+    archive.visit_properties_mut(&mut |property| {
+        if let PropertyData::Int32(value) = &mut property.data {
+            *value += 1;
+        }
+    });
+
+    let quantity = container(&archive).actors[&9].archive.object_index[0].get_int("Quantity").unwrap();
+
+    assert_eq!(quantity, 10);
+}
+
+// the actors map has no order of its own, nested archives are listed by actor id like visit_properties does
+#[test]
+fn nested_archives_are_listed_by_actor_id() {
+    let mut archive = world_archive();
+    let expected = ["/Game/_Core/Blueprints/Base/BP_RemnantSaveGame", "/Game/World_Base/Actors/Actor_2", "/Game/World_Base/Actors/Actor_5", "/Game/World_Base/Actors/Actor_9"];
+
+    let paths = archive.content.archives().iter().map(|nested| nested.object_index[0].object_path.clone()).collect::<Vec<_>>();
+    assert_eq!(paths, expected);
+
+    let positions = archive.content.actors().iter().map(|actor| actor.transform.as_ref().unwrap().position.x).collect::<Vec<_>>();
+    assert_eq!(positions, [2.0, 5.0, 9.0]);
+
+    let mut paths = Vec::new();
+
+    archive.content.for_each_archive_mut(&mut |nested| {
+        paths.push(nested.object_index[0].object_path.clone());
+        Ok(())
+    }).unwrap();
+
+    assert_eq!(paths, expected);
+}
+
+#[test]
+fn unsupported_container_versions_are_rejected() {
+    let mut archive = world_archive();
+    let content = |archive: &SaveGameArchive| read_sav(write_sav(archive, &Compressor::None)).get_content().unwrap();
+
+    let current = content(&archive);
+
+    let Some(PropertyData::Struct(persistence)) = archive.root_mut().unwrap().get_path_mut("PersistenceData").unwrap() else {
+        panic!("expected a struct");
+    };
+    let StructData::PersistenceContainer(container) = &mut persistence.data else {
+        panic!("expected a persistence container");
+    };
+
+    container.version = 3;
+
+    // past the checksum, the container version is the only byte that differs
+    let older = content(&archive);
+    let offset = (4..current.len()).find(|&i| current[i] != older[i]).unwrap();
+
+    assert_eq!(current[offset..offset + 4], 4u32.to_le_bytes());
+
+    let mut unsupported = current;
+    unsupported[offset] = 9;
+
+    let error = format!("{:#}", SaveGameArchive::read(&mut Reader::new(unsupported, ObjectPadding::Four)).unwrap_err());
+    assert!(error.contains("Unsupported persistence container version: 9 (supported: 1-4)"), "{}", error);
+
+    let Some(PropertyData::Struct(persistence)) = archive.root_mut().unwrap().get_path_mut("PersistenceData").unwrap() else {
+        panic!("expected a struct");
+    };
+    let StructData::PersistenceContainer(container) = &mut persistence.data else {
+        panic!("expected a persistence container");
+    };
+
+    container.version = 0;
+
+    let error = format!("{:#}", SavFile::write(&mut Writer::new(Vec::new(), ObjectPadding::Four), &archive, &Compressor::None).unwrap_err());
+    assert!(error.contains("Unsupported persistence container version: 0"), "{}", error);
+}
+
+#[test]
+fn actor_rotations_are_typed_structs() {
+    let rotation = |name: &str, struct_name: &str, data: Value| property(name, "StructProperty", json!({ "Struct": {
+        "struct_name": { "value": struct_name },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { struct_name: data }
+    } }));
+
+    let mut world = serde_json::to_value(world_archive()).unwrap();
+    let actor_properties = "/content/object_index/0/properties/0/data/Struct/data/PersistenceContainer/actors/2/archive/object_index/0/properties";
+
+    world.pointer_mut(actor_properties).unwrap().as_array_mut().unwrap().extend([
+        rotation("StartingRotation", "Quat", json!({ "w": 0.5, "x": -0.5, "y": 0.5, "z": 0.5 })),
+        rotation("Orientation", "Rotator", json!({ "pitch": 0.0, "yaw": -90.0, "roll": 12.5 })),
+    ]);
+
+    let archive: SaveGameArchive = serde_json::from_value(world).unwrap();
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    let actor = &container(&read).actors[&2];
+    let root = &actor.archive.object_index[0];
+
+    // a quaternion is four doubles and a rotator three, neither is a tagged property list
+    assert_eq!(root.get("StartingRotation").unwrap().size, 32);
+    assert!(matches!(root.get_struct("StartingRotation").unwrap().data, StructData::Quat(_)));
+    assert_eq!(root.get("Orientation").unwrap().size, 24);
+    assert!(matches!(root.get_struct("Orientation").unwrap().data, StructData::Rotator(_)));
+
+    assert!(read.semantic_eq(&archive).unwrap());
+    round_trip("actor rotations", bytes);
+}
+
+// the index lists the actor infos first and the destroyed ids after them, the dynamic actors follow
+#[test]
+fn container_index_lists_infos_before_destroyed_ids() {
+    let mut world = serde_json::to_value(world_archive()).unwrap();
+    *world.pointer_mut("/content/object_index/0/properties/0/data/Struct/data/PersistenceContainer/destroyed").unwrap() = json!([7, 11]);
+
+    let archive: SaveGameArchive = serde_json::from_value(world).unwrap();
+    let bytes = write_sav(&archive, &Compressor::None);
+    let content = read_sav(bytes.clone()).get_content().unwrap();
+
+    let infos = [3u32.to_le_bytes().as_slice(), &2u64.to_le_bytes()].concat();
+    let start = content.windows(infos.len()).position(|window| window == infos).unwrap();
+
+    // three infos of a u64 id and two u32s, then the destroyed list and the dynamic actor count
+    let destroyed = [2u32.to_le_bytes().as_slice(), &7u64.to_le_bytes(), &11u64.to_le_bytes(), &2u32.to_le_bytes()].concat();
+    let end = start + 4 + 3 * 16;
+
+    assert_eq!(content[end..end + destroyed.len()], destroyed);
+
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    assert_eq!(container(&read).destroyed, [7, 11]);
+    assert_eq!(container(&read).actors.len(), 3);
+
+    round_trip("container index", bytes);
+}
+
+#[test]
+fn sample_archive_round_trips() {
+    let archive = sample_archive();
+
+    for compressor in [Compressor::Zlib, Compressor::Gzip, Compressor::LZ4, Compressor::None] {
+        let bytes = write_sav(&archive, &compressor);
+
+        SavFile::verify(&bytes).expect("written sample has a valid header");
+        let read = read_sav(bytes.clone()).get_archive().expect("read sample archive");
+
+        assert!(read.semantic_eq(&archive).unwrap(), "sample ({:?}) changed on write", compressor);
+
+        // a rotator is stored as three doubles, not as a tagged property list
+        assert_eq!(read.root().unwrap().get("SpawnRotation").unwrap().size, 24);
+
+        round_trip(&format!("sample ({:?})", compressor), bytes);
+    }
+}
+
+#[test]
+fn verify_rejects_a_bad_checksum() {
+    let mut bytes = write_sav(&sample_archive(), &Compressor::None);
+
+    bytes[0] ^= 0xFF;
+
+    assert!(SavFile::verify(&bytes).is_err());
+}
+
+#[test]
+fn unchecked_content_ignores_a_bad_checksum() {
+    let mut bytes = write_sav(&sample_archive(), &Compressor::None);
+
+    bytes[0] ^= 0xFF;
+
+    let sav_file = read_sav(bytes);
+    let content = sav_file.get_content_unchecked().unwrap();
+
+    assert!(sav_file.get_content().is_err());
+    assert!(SaveGameArchive::read(&mut Reader::new(content, ObjectPadding::Four)).is_ok());
+}
+
+// struct references are written raw inside maps, a tagged one is an error rather than a panic
+#[test]
+fn struct_reference_outside_a_map_is_rejected() {
+    let mut archive = sample_archive();
+
+    archive.root_mut().unwrap().properties.push(serde_json::from_value(property(
+        "Reference",
+        "StructProperty",
+        json!({ "StructReference": { "a": 1, "b": 2, "c": 3, "d": 4 } }),
+    )).unwrap());
+
+    assert!(SavFile::write(&mut Writer::new(Vec::new(), ObjectPadding::Four), &archive, &Compressor::None).is_err());
+}
+
+#[test]
+fn dump_tree_prints_nested_values() {
+    let mut out = Vec::new();
+
+    world_archive().dump_tree(&mut out, usize::MAX).unwrap();
+    sample_archive().dump_tree(&mut out, 2).unwrap();
+
+    let tree = String::from_utf8(out).unwrap();
+
+    assert!(tree.contains("PersistenceData: PersistenceContainer [3 actors, 1 destroyed]"));
+    assert!(tree.contains(&format!("actor 5 ({})", DROPPED_ITEM_CLASS)));
+    assert!(tree.contains("      Quantity: 5"));
+    assert!(tree.contains("LoadoutTags: Array<EnumProperty> [2 elements]"));
+    assert!(tree.contains("trailing data: [4 bytes]"));
+    assert!(tree.contains("SpawnRotation: (pitch -12.5, yaw 90, roll 0.25)"));
+    assert!(tree.contains("      ..."));
+}
+
+#[test]
+fn diff_reports_nested_changes() {
+    let old = world_archive();
+    let mut new = world_archive();
+
+    assert!(old.diff(&new).unwrap().is_empty());
+
+    let root = new.root_mut().unwrap();
+    let Some(PropertyData::Struct(property)) = root.get_path_mut("PersistenceData").unwrap() else {
+        panic!("expected a struct");
+    };
+    let StructData::PersistenceContainer(container) = &mut property.data else {
+        panic!("expected a persistence container");
+    };
+
+    container.actors.remove(&2);
+    container.actors.get_mut(&5).unwrap().archive.object_index[0].set_int("Quantity", 6).unwrap();
+
+    let changes = old.diff(&new).unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    let container_path = "/Game/_Core/Blueprints/Base/BP_RemnantSaveGame:PersistenceData";
+
+    assert_eq!(changes.len(), 2, "{:#?}", changes);
+    assert!(changes[0].starts_with(&format!("- {}[2] = ", container_path)));
+    assert_eq!(
+        changes[1],
+        format!(r#"~ {}[5] > /Game/World_Base/Actors/Actor_5:Quantity: {{"Int32":5}} -> {{"Int32":6}}"#, container_path),
+    );
+}
+
+// IntVector and LinearColor are fixed layouts, not tagged property lists
+#[test]
+fn fixed_layout_structs_use_their_binary_layout() {
+    let sav_file = read_sav(write_sav(&sample_archive(), &Compressor::None));
+    let content = sav_file.get_content().unwrap();
+
+    let int_vector: &[u8] = &[
+        0x01, 0x00, 0x00, 0x00,
+        0xFE, 0xFF, 0xFF, 0xFF,
+        0x03, 0x00, 0x00, 0x00,
+    ];
+    let linear_color: &[u8] = &[
+        0x00, 0x00, 0x80, 0x3F,
+        0x00, 0x00, 0x00, 0x3F,
+        0x00, 0x00, 0x80, 0x3E,
+        0x00, 0x00, 0x80, 0x3F,
+    ];
+
+    assert!(content.windows(int_vector.len()).any(|window| window == int_vector));
+    assert!(content.windows(linear_color.len()).any(|window| window == linear_color));
+
+    let read = sav_file.get_archive().unwrap();
+    let root = read.root().unwrap();
+
+    assert_eq!(root.get("GridCell").unwrap().size, 12);
+    assert_eq!(root.get("DyeColor").unwrap().size, 16);
+}
+
+// bounding volumes are doubles followed by the validity flag, Box2D stores it as a 4 byte bool
+#[test]
+fn bounds_structs_use_their_binary_layout() {
+    let bounds = |struct_name: &str, data: Value| json!({ "Struct": {
+        "struct_name": { "value": struct_name },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { struct_name: data }
+    } });
+
+    let mut archive = sample_archive();
+    let properties = [
+        property("Bounds", "StructProperty", bounds("Box", json!({
+            "min": { "x": -1.0, "y": -2.0, "z": -3.0 },
+            "max": { "x": 1.0, "y": 2.0, "z": 3.0 },
+            "is_valid": 1
+        }))),
+        property("Area", "StructProperty", bounds("Box2D", json!({
+            "min": { "x": 0.0, "y": 0.0 },
+            "max": { "x": 4.0, "y": 8.0 },
+            "is_valid": 1
+        }))),
+        property("Reach", "StructProperty", bounds("Sphere", json!({
+            "center": { "x": 1.0, "y": 1.0, "z": 1.0 },
+            "w": 2.5
+        }))),
+    ];
+
+    for property in properties {
+        archive.content.object_index[0].properties.push(serde_json::from_value(property).unwrap());
+    }
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+    let root = read.root().unwrap();
+
+    assert_eq!(root.get("Bounds").unwrap().size, 49);
+    assert_eq!(root.get("Area").unwrap().size, 36);
+    assert_eq!(root.get("Reach").unwrap().size, 32);
+    assert!(matches!(&root.get_struct("Reach").unwrap().data, StructData::Sphere(sphere) if sphere.w == 2.5));
+
+    round_trip("bounds structs", bytes);
+}
+
+// struct keyed maps store their keys as bare guids right after the entry count
+#[test]
+fn struct_map_keys_are_written_as_guids() {
+    let content = read_sav(write_sav(&sample_archive(), &Compressor::None)).get_content().unwrap();
+
+    let entries: &[u8] = &[
+        0x00, 0x00, 0x00, 0x00, // removed entries
+        0x01, 0x00, 0x00, 0x00, // entry count
+        0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+    ];
+
+    assert!(content.windows(entries.len()).any(|window| window == entries));
+
+    let mut archive = sample_archive();
+    let Some(PropertyData::Map(map)) = archive.root_mut().unwrap().get_path_mut("PersistenceKeys").unwrap() else {
+        panic!("expected a map");
+    };
+
+    map.elements[0].0 = PropertyData::Int32(1);
+
+    assert!(SavFile::write(&mut Writer::new(Vec::new(), ObjectPadding::Four), &archive, &Compressor::None).is_err());
+}
+
+#[test]
+fn scrubbed_saves_change_identifiers_and_stay_writable() {
+    let guid = json!({ "a": 1, "b": 2, "c": 3, "d": 4 });
+    let mut archive = sample_archive();
+
+    archive.content.object_index[0].properties.extend([
+        property("AccountId", "Int64Property", json!({ "Int64": 76561198000000000i64 })),
+        property("PlatformName", "StrProperty", json!({ "Str": "Steam" })),
+        property("AccountGuid", "StructProperty", json!({ "Struct": {
+            "struct_name": { "value": "Guid" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+            "data": { "Guid": guid }
+        } })),
+        property("FriendCode", "StrProperty", json!({ "Str": "ABCD-1234" })),
+    ].map(|property| serde_json::from_value(property).unwrap()));
+
+    let mut scrubber = Scrubber::default();
+    scrubber.properties.push("FriendCode".to_owned());
+
+    assert_eq!(scrubber.scrub(&mut archive), 4);
+
+    let bytes = write_sav(&archive, &Compressor::Zlib);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+    let root = read.root().unwrap();
+
+    assert!(matches!(root.get("AccountId").unwrap().data, PropertyData::Int64(0)));
+    assert_eq!(root.get_str("PlatformName").unwrap(), "");
+    assert_eq!(root.get_str("FriendCode").unwrap(), "");
+    assert!(!matches!(&root.get_struct("AccountGuid").unwrap().data, StructData::Guid(value) if *value == serde_json::from_value(guid.clone()).unwrap()));
+
+    // names aren't scrubbed by default
+    assert_eq!(root.get_str("CharacterName").unwrap(), "Странник 旅人");
+    assert!(read.semantic_eq(&archive).unwrap());
+
+    round_trip("scrubbed", bytes);
+}
+
+#[test]
+fn anonymize_replaces_names_and_stays_writable() {
+    let mut archive = sample_archive();
+
+    assert_eq!(archive.anonymize(), 1);
+
+    let bytes = write_sav(&archive, &Compressor::Zlib);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    assert_eq!(read.root().unwrap().get_str("CharacterName").unwrap(), "Anonymous");
+    assert!(read.semantic_eq(&archive).unwrap());
+
+    round_trip("anonymized", bytes);
+}
+
+// preserved duplicates keep resolving to their first index, new names are appended once
+#[test]
+fn preserved_name_table_reuses_existing_names() {
+    let mut archive = sample_archive();
+
+    archive.content.name_table.list = vec!["Level".to_owned(), "Level".to_owned(), "Unused".to_owned()];
+
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+    writer.preserve_name_table = true;
+
+    SavFile::write(&mut writer, &archive, &Compressor::None).unwrap();
+
+    let read = read_sav(writer.into_inner()).get_archive().unwrap();
+    let names = &read.content.name_table.list;
+
+    assert_eq!(names[..3], archive.content.name_table.list[..]);
+    assert_eq!(names.iter().filter(|name| *name == "Level").count(), 2);
+    assert!(read.semantic_eq(&archive).unwrap());
+}
+
+// names don't remember the entry they were read from, so duplicates are kept in the table but not referenced
+#[test]
+fn preserved_duplicates_are_written_with_their_first_index() {
+    let mut archive = sample_archive();
+
+    archive.content.name_table.list = vec!["Unused".to_owned(), "Level".to_owned(), "Level".to_owned()];
+
+    let write = |archive: &SaveGameArchive| {
+        let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+        writer.preserve_name_table = true;
+        SavFile::write(&mut writer, archive, &Compressor::None).unwrap();
+
+        writer.into_inner()
+    };
+
+    let bytes = write(&archive);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+    let names = &read.content.name_table.list;
+
+    // the table is kept as it was and written again unchanged
+    assert_eq!(names[..3], ["Unused", "Level", "Level"]);
+    assert_content_eq("preserved duplicates", &read_sav(bytes.clone()).get_content().unwrap(), &read_sav(write(&read)).get_content().unwrap());
+
+    // the Level tag, then its size, array index, guid flag and value
+    let int_property = names.iter().position(|name| name == "IntProperty").unwrap() as u16;
+    let tag = [1u16.to_le_bytes().as_slice(), &int_property.to_le_bytes(), &4u32.to_le_bytes(), &0u32.to_le_bytes(), &[0], &20i32.to_le_bytes()].concat();
+
+    assert!(read_sav(bytes).get_content().unwrap().windows(tag.len()).any(|window| window == tag));
+}
+
+#[test]
+fn name_table_overflow_is_rejected() {
+    let mut archive = sample_archive();
+
+    archive.content.name_table.list = (0..=0x7FFF).map(|i| format!("Name_{}", i)).collect();
+
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+    writer.preserve_name_table = true;
+
+    let error = SavFile::write(&mut writer, &archive, &Compressor::None).unwrap_err();
+
+    assert!(error.to_string().contains("at most 32768 unique names"), "{}", error);
+}
+
+#[test]
+fn non_ascii_names_are_written_as_utf16() {
+    let mut archive = sample_archive();
+
+    archive.content.object_index[0].properties.push(
+        serde_json::from_value(property("Companion", "NameProperty", json!({ "Name": { "value": "Пёс_旅" } }))).unwrap(),
+    );
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone());
+
+    assert!(read.get_archive().unwrap().content.name_table.list.iter().any(|name| name == "Пёс_旅"));
+
+    // a negative length counts UTF-16 code units, the terminator included
+    let mut entry = (-6i32).to_le_bytes().to_vec();
+    entry.extend("Пёс_旅\0".encode_utf16().flat_map(u16::to_le_bytes));
+
+    let content = read.get_content().unwrap();
+    assert!(content.windows(entry.len()).any(|window| window == entry));
+
+    round_trip("non-ASCII names", bytes);
+}
+
+#[test]
+fn numbered_names_round_trip() {
+    for name in ["Level_2", "Level", "Level_02", "_2", "Level_0", "Level_Two"] {
+        assert_eq!(name.parse::<FName>().unwrap().to_display_string(), name);
+    }
+
+    assert_eq!("Level_2".parse::<FName>().unwrap(), FName { value: "Level".to_owned(), number: Some(2) });
+    assert_eq!("Level_02".parse::<FName>().unwrap().number, None);
+
+    let mut archive = sample_archive();
+
+    archive.root_mut().unwrap().get_mut("Level").unwrap().name = "Level_2".parse().unwrap();
+
+    let read = read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap();
+    let root = read.root().unwrap();
+
+    assert_eq!(root.get("Level").unwrap().name.number, Some(2));
+    assert_eq!(root.get_int("Level_2"), Some(20));
+    assert_eq!(root.get_int("Level_3"), None);
+}
+
+// the archive content with the second LoadoutTags element pointing past the name table,
+// along with the offset of the array elements
+fn corrupt_loadout_tags(archive: &SaveGameArchive) -> (Vec<u8>, usize) {
+    let bytes = write_sav(archive, &Compressor::None);
+    let names = read_sav(bytes.clone()).get_archive().unwrap().content.name_table.list;
+    let index = |name: &str| names.iter().position(|n| n == name).unwrap() as u16;
+
+    let mut content = read_sav(bytes).get_content().unwrap();
+    let tags = [index("ELoadoutTag::Primary").to_le_bytes(), index("ELoadoutTag::Melee").to_le_bytes()].concat();
+    let offset = content.windows(tags.len()).position(|window| window == tags).unwrap();
+
+    content[offset + 2..offset + 4].copy_from_slice(&0x7FFFu16.to_le_bytes());
+
+    (content, offset)
+}
+
+#[test]
+fn read_errors_name_where_parsing_failed() {
+    let (content, offset) = corrupt_loadout_tags(&sample_archive());
+
+    let error = format!("{:#}", SaveGameArchive::read(&mut Reader::new(content, ObjectPadding::Four)).unwrap_err());
+
+    assert!(error.contains("while parsing object 0 (/Game/_Core/Blueprints/Base/BP_RemnantSaveGameProfile)"), "{}", error);
+    assert!(error.contains("while parsing property LoadoutTags (ArrayProperty)"), "{}", error);
+    assert!(error.contains("while parsing Array element 1 at offset"), "{}", error);
+    assert!(error.contains("Name index 32767 is out of range"), "{}", error);
+
+    // the parallel reader parses each object on its own, so offsets within it are relative
+    if !cfg!(feature = "parallel") {
+        assert!(error.contains(&format!("element 1 at offset {:#X}", offset + 2)), "{}", error);
+    }
+}
+
+#[test]
+fn raw_fallback_keeps_broken_properties_as_bytes() {
+    let (content, _) = corrupt_loadout_tags(&sample_archive());
+
+    let mut reader = Reader::new(content.clone(), ObjectPadding::Four);
+    reader.raw_fallback = true;
+
+    let archive = SaveGameArchive::read(&mut reader).expect("read with raw fallback");
+    let root = archive.root().unwrap();
+
+    assert!(matches!(root.get("LoadoutTags").unwrap().data, PropertyData::Raw(_)));
+    assert_eq!(root.get_int("Level"), Some(20));
+
+    // the raw bytes hold name indices, so they only stay valid with the original name table
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+    writer.preserve_name_table = true;
+
+    SavFile::write(&mut writer, &archive, &Compressor::None).unwrap();
+
+    // the corrupted content still has the checksum of the original
+    let written = read_sav(writer.into_inner()).get_content().unwrap();
+
+    assert_content_eq("raw fallback", &content[4..], &written[4..]);
+}
+
+// raw bytes hold name indices, a rebuilt name table would point them at other names
+#[test]
+fn raw_properties_need_the_original_name_table() {
+    let (content, _) = corrupt_loadout_tags(&sample_archive());
+
+    let mut reader = Reader::new(content.clone(), ObjectPadding::Four);
+    reader.raw_fallback = true;
+
+    let archive = SaveGameArchive::read(&mut reader).unwrap();
+
+    assert!(archive.has_raw_properties());
+
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+    let error = SavFile::write(&mut writer, &archive, &Compressor::None).unwrap_err();
+
+    assert!(error.to_string().contains("original name table"), "{:#}", error);
+
+    // what repack does for such saves, the read back has to use the raw fallback as well
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+    writer.preserve_name_table = archive.has_raw_properties();
+
+    SavFile::write_verified(&mut writer, &archive, &Compressor::Zlib).unwrap();
+
+    let written = read_sav(writer.into_inner()).get_content().unwrap();
+
+    assert_content_eq("repacked raw fallback", &content[4..], &written[4..]);
+}
+
+#[test]
+fn lenient_read_skips_broken_objects() {
+    let mut archive = sample_archive();
+
+    archive.content.object_index[1].properties.push(
+        serde_json::from_value(property("Stage", "IntProperty", json!({ "Int32": 7 }))).unwrap(),
+    );
+
+    let (content, _) = corrupt_loadout_tags(&archive);
+
+    assert!(SaveGameArchive::read(&mut Reader::new(content.clone(), ObjectPadding::Four)).is_err());
+
+    let (read, errors) = SaveGameArchive::read_lenient(&mut Reader::new(content, ObjectPadding::Four)).unwrap();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].object_id, 0);
+    assert!(errors[0].message.contains("LoadoutTags"), "{}", errors[0].message);
+
+    assert!(read.content.object_index[0].properties.is_empty());
+    assert_eq!(read.content.object_index[1].get_int("Stage"), Some(7));
+
+    assert!(read.content.partial);
+    assert!(SavFile::write(&mut Writer::new(Vec::new(), ObjectPadding::Four), &read, &Compressor::None).is_err());
+}
+
+// the object data is followed by the index and name table, so a cut inside an object is simulated by corrupting it
+#[test]
+fn partial_recovery_keeps_objects_before_the_cut() {
+    let mut archive = sample_archive();
+    let position = archive.content.object_index[0].properties.iter().position(|property| property.name.value == "LoadoutTags").unwrap();
+    let loadout_tags = archive.content.object_index[0].properties.remove(position);
+
+    archive.content.object_index[1].properties.push(loadout_tags);
+
+    let (content, _) = corrupt_loadout_tags(&archive);
+
+    assert!(SaveGameArchive::read(&mut Reader::new(content.clone(), ObjectPadding::Four)).is_err());
+
+    let mut reader = Reader::new(content, ObjectPadding::Four);
+    reader.partial_recovery = true;
+
+    let read = SaveGameArchive::read(&mut reader).expect("recover the objects before the cut");
+
+    assert!(read.content.partial);
+    assert_eq!(read.content.object_index.len(), 2);
+    assert_eq!(read.root().unwrap().get_int("Level"), Some(20));
+
+    let error = SavFile::write(&mut Writer::new(Vec::new(), ObjectPadding::Four), &read, &Compressor::None).unwrap_err();
+    assert_eq!(error.to_string(), "Refusing to write a partially recovered archive");
+}
+
+#[test]
+fn bad_property_padding_is_an_error() {
+    let mut content = read_sav(write_sav(&sample_archive(), &Compressor::None)).get_content().unwrap();
+
+    // the padding after the root object's properties, followed by its trailing data
+    let offset = content.windows(8).position(|window| window == [0, 0, 0, 0, 1, 2, 3, 4]).unwrap();
+
+    content[offset] = 0xFF;
+
+    let error = format!("{:#}", SaveGameArchive::read(&mut Reader::new(content, ObjectPadding::Four)).unwrap_err());
+
+    assert!(error.contains("Object 0 has non-zero padding 0xFF"), "{}", error);
+}
+
+#[test]
+fn offsets_past_the_end_are_rejected() {
+    let mut content = read_sav(write_sav(&sample_archive(), &Compressor::None)).get_content().unwrap();
+
+    // header, package version and the two strings of the class path come before the name table offset
+    let class_path = "/Game/_Core/Blueprints/Base/BP_RemnantSaveGameProfile";
+    let field = 16 + 8 + (4 + class_path.len() + 1) + (4 + "BP_RemnantSaveGameProfile_C".len() + 1);
+    let name_table_offset = u64::from_le_bytes(content[field..field + 8].try_into().unwrap());
+
+    assert!(name_table_offset < content.len() as u64);
+
+    let past_the_end = content.len() as u64 + 1;
+
+    content[field..field + 8].copy_from_slice(&past_the_end.to_le_bytes());
+
+    let mut reader = Reader::new(content, ObjectPadding::Four);
+
+    assert_eq!(reader.remaining(), reader.len());
+    assert!(reader.seek_checked(reader.len()).is_ok());
+    assert_eq!(reader.remaining(), 0);
+    assert!(reader.seek_checked(0).is_ok());
+
+    let error = format!("{:#}", SaveGameArchive::read(&mut reader).unwrap_err());
+
+    assert!(error.contains("Invalid name table offset"), "{}", error);
+    assert!(error.contains("is past the end of the data"), "{}", error);
+}
+
+// PC saves are little-endian, the byte order is only chosen through io::Endian
+#[test]
+fn saves_are_written_little_endian() {
+    assert_eq!(<Endian as ByteOrder>::read_u32(&[0x78, 0x56, 0x34, 0x12]), 0x12345678);
+
+    let archive = sample_archive();
+    let bytes = write_sav(&archive, &Compressor::None);
+
+    // the package tag of the first chunk follows the sav header
+    assert_eq!(bytes[12..20], [0xC1, 0x83, 0x2A, 0x9E, 0x22, 0x22, 0x22, 0x22]);
+
+    let content = read_sav(bytes).get_content().unwrap();
+
+    assert_eq!(content[4..8], (content.len() as u32).to_le_bytes());
+    assert_eq!(content[8..12], [9, 0, 0, 0]);
+    assert_eq!(content[12..16], 400000u32.to_le_bytes());
+    assert_eq!(content[16..20], 522u32.to_le_bytes());
+}
+
+#[test]
+fn header_reads_without_the_archive() {
+    let sav_file = read_sav(write_sav(&sample_archive(), &Compressor::Zlib));
+    let header = sav_file.read_header().unwrap();
+
+    assert_eq!(header.save_game_file_version, 9);
+    assert_eq!(header.build_number, 400000);
+
+    let archive = sav_file.get_archive().unwrap();
+
+    assert_eq!((archive.save_version(), archive.build_number()), (9, 400000));
+    assert_eq!(archive.version().unwrap(), SaveVersion::V9);
+}
+
+// the version is detected once from the header, the object layout decisions are made through it
+#[test]
+fn readers_get_the_save_version_of_the_header() {
+    let content = read_sav(write_sav(&world_archive(), &Compressor::None)).get_content().unwrap();
+    let mut reader = Reader::new(content, ObjectPadding::Four);
+
+    SaveGameArchive::read(&mut reader).unwrap();
+
+    assert_eq!(reader.save_version, SaveVersion::V9);
+    assert_eq!(SaveVersion::V9.terminator_size(ObjectPadding::Eight, 0), 8);
+    assert_eq!(SaveVersion::V9.terminator_size(ObjectPadding::Eight, 1), 4);
+    assert!(SaveVersion::V9.has_loaded_data(false));
+    assert!(!SaveVersion::V9.has_loaded_data(true));
+}
+
+#[test]
+fn changed_build_numbers_round_trip() {
+    let mut archive = sample_archive();
+
+    archive.set_build_number(412345);
+    assert_eq!(archive.build_number(), 412345);
+
+    let bytes = write_sav(&archive, &Compressor::Zlib);
+    let sav_file = read_sav(bytes.clone());
+
+    assert_eq!(sav_file.read_header().unwrap().build_number, 412345);
+    assert_eq!(sav_file.get_archive().unwrap().build_number(), 412345);
+
+    round_trip("build number", bytes);
+}
+
+#[test]
+fn unknown_save_versions_are_rejected() {
+    let mut archive = sample_archive();
+
+    archive.header.save_game_file_version = 10;
+
+    let error = read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap_err();
+
+    assert_eq!(error.to_string(), "Unsupported save game file version: 10 (build 400000, supported: 9)");
+}
+
+#[test]
+fn older_save_versions_are_rejected_with_their_number() {
+    for version in [0, 8] {
+        let mut archive = sample_archive();
+
+        archive.header.save_game_file_version = version;
+
+        let sav_file = read_sav(write_sav(&archive, &Compressor::None));
+        let expected = format!("Unsupported save game file version: {} ", version);
+
+        assert!(sav_file.read_header().unwrap_err().to_string().starts_with(&expected));
+        assert!(sav_file.get_archive().unwrap_err().to_string().starts_with(&expected));
+    }
+}
+
+// inner arrays repeat their inner type but not the tag's trailing byte
+#[test]
+fn nested_arrays_round_trip() {
+    let inner = |values: &[i32]| json!({ "Array": {
+        "inner_type": { "value": "IntProperty" },
+        "head_data": "None",
+        "elements": values.iter().map(|value| json!({ "Int32": value })).collect::<Vec<_>>()
+    } });
+
+    let mut archive = sample_archive();
+    let nested = serde_json::from_value(property("Grid", "ArrayProperty", json!({ "Array": {
+        "inner_type": { "value": "ArrayProperty" },
+        "head_data": "None",
+        "elements": [inner(&[1, 2]), inner(&[]), inner(&[3])]
+    } }))).unwrap();
+
+    archive.content.object_index[0].properties.push(nested);
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    let Some(PropertyData::Array(grid)) = read.root().unwrap().get_path("Grid").unwrap() else {
+        panic!("expected an array");
+    };
+
+    assert_eq!(grid.elements.len(), 3);
+    assert!(read.semantic_eq(&archive).unwrap());
+
+    round_trip("nested arrays", bytes);
+}
+
+// array elements take their struct name from the head, fixed-layout structs included
+#[test]
+fn date_time_arrays_round_trip() {
+    let date_time = |value: &str| json!({ "Struct": {
+        "struct_name": { "value": "DateTime" },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { "DateTime": value }
+    } });
+
+    let mut archive = sample_archive();
+    let unlocks = serde_json::from_value(property("UnlockTimes", "ArrayProperty", json!({ "Array": {
+        "inner_type": { "value": "StructProperty" },
+        "head_data": { "Struct": {
+            "name": { "value": "UnlockTimes" },
+            "type_name": { "value": "StructProperty" },
+            "index": 0,
+            "struct_name": { "value": "DateTime" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 }
+        } },
+        "elements": [date_time("2023-07-25T12:34:56Z"), date_time("2024-01-02T03:04:05.5Z")]
+    } }))).unwrap();
+
+    archive.content.object_index[0].properties.push(unlocks);
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+    let unlocks = read.root().unwrap().get("UnlockTimes").unwrap();
+
+    // the count, the struct head and two 8 byte tick counts
+    assert_eq!(unlocks.size, 4 + (2 + 2 + 4 + 4 + 2 + 16 + 1) + 2 * 8);
+
+    let PropertyData::Array(array) = &unlocks.data else {
+        panic!("expected an array");
+    };
+
+    assert!(array.elements.iter().all(|element| matches!(element, PropertyData::Struct(date_time) if matches!(date_time.data, StructData::DateTime(_)))));
+    assert!(read.semantic_eq(&archive).unwrap());
+
+    round_trip("date time arrays", bytes);
+}
+
+#[test]
+fn struct_elements_are_added_and_removed() {
+    let cell = |x: i32| json!({ "Struct": {
+        "struct_name": { "value": "IntVector" },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { "IntVector": { "x": x, "y": 0, "z": 0 } }
+    } });
+
+    let mut archive = sample_archive();
+    let cells = serde_json::from_value(property("Cells", "ArrayProperty", json!({ "Array": {
+        "inner_type": { "value": "StructProperty" },
+        "head_data": { "Struct": {
+            "name": { "value": "Cells" },
+            "type_name": { "value": "StructProperty" },
+            "index": 0,
+            "struct_name": { "value": "IntVector" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 }
+        } },
+        "elements": [cell(1), cell(2)]
+    } }))).unwrap();
+
+    archive.content.object_index[0].properties.push(cells);
+
+    let Some(PropertyData::Array(cells)) = archive.root_mut().unwrap().get_path_mut("Cells").unwrap() else {
+        panic!("expected an array");
+    };
+
+    cells.push_struct(serde_json::from_value(json!({ "IntVector": { "x": 3, "y": 0, "z": 0 } })).unwrap()).unwrap();
+    assert!(cells.push(PropertyData::Int32(4)).is_err());
+    assert!(cells.remove(3).is_err());
+    cells.remove(0).unwrap();
+
+    // the element count and head size are derived when writing
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+    let cells = read.root().unwrap().get("Cells").unwrap();
+
+    assert_eq!(cells.size, 4 + (2 + 2 + 4 + 4 + 2 + 16 + 1) + 2 * 12);
+
+    let PropertyData::Array(array) = &cells.data else {
+        panic!("expected an array");
+    };
+
+    let xs = array.elements.iter()
+        .map(|element| match element {
+            PropertyData::Struct(property) => match &property.data {
+                StructData::IntVector(vector) => vector.x,
+                data => panic!("expected an int vector, got {:?}", data),
+            },
+            _ => panic!("expected a struct, got {:?}", element),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(xs, [2, 3]);
+    round_trip("struct array edits", bytes);
+}
+
+#[test]
+fn soft_object_arrays_round_trip() {
+    let paths = [
+        "/Game/World_Base/Items/Weapons/Weapon_Repeater.Weapon_Repeater_C",
+        "",
+        "/Game/World_Base/Items/Relics/Relic_Dragonheart.Relic_Dragonheart_C",
+    ];
+
+    let mut archive = sample_archive();
+    let equipped = serde_json::from_value(property("EquippedItems", "ArrayProperty", json!({ "Array": {
+        "inner_type": { "value": "SoftObjectProperty" },
+        "head_data": "None",
+        "elements": paths.iter().map(|path| json!({ "SoftObject": path })).collect::<Vec<_>>()
+    } }))).unwrap();
+
+    archive.content.object_index[0].properties.push(equipped);
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    let Some(PropertyData::Array(equipped)) = read.root().unwrap().get_path("EquippedItems").unwrap() else {
+        panic!("expected an array");
+    };
+
+    let read_paths = equipped.elements.iter()
+        .map(|element| match element {
+            PropertyData::SoftObject(path) => path.as_str(),
+            _ => panic!("expected a soft object path, got {:?}", element),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(read_paths, paths);
+    assert!(read.semantic_eq(&archive).unwrap());
+
+    round_trip("soft object arrays", bytes);
+}
+
+// malformed saves can nest values arbitrarily deep, which has to fail instead of overflowing the stack
+#[test]
+fn deeply_nested_arrays_are_rejected() {
+    let nested = |depth: usize| {
+        let mut array = json!({ "Array": { "inner_type": { "value": "IntProperty" }, "head_data": "None", "elements": [] } });
+
+        for _ in 1..depth {
+            array = json!({ "Array": { "inner_type": { "value": "ArrayProperty" }, "head_data": "None", "elements": [array] } });
+        }
+
+        let mut archive = sample_archive();
+
+        archive.content.object_index[0].properties.push(serde_json::from_value(property("Grid", "ArrayProperty", array)).unwrap());
+
+        read_sav(write_sav(&archive, &Compressor::None)).get_archive()
+    };
+
+    assert!(nested(50).is_ok());
+
+    let error = format!("{:#}", nested(100).unwrap_err());
+
+    assert!(error.contains("nested deeper than"), "{}", error);
+
+    // structs go through the most frames per level, deep enough to hit the limit, but
+    // not so deep that deserializing the JSON overflows the stack itself
+    let mut value = json!({ "Int32": 1 });
+    let mut type_name = "IntProperty";
+
+    for _ in 0..70 {
+        value = json!({ "Struct": {
+            "struct_name": { "value": "Nested" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+            "data": { "Dynamic": { "properties": [property("Inner", type_name, value)] } }
+        } });
+        type_name = "StructProperty";
+    }
+
+    let mut archive = sample_archive();
+
+    archive.content.object_index[0].properties.push(serde_json::from_value(property("Tree", type_name, value)).unwrap());
+
+    let error = format!("{:#}", read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap_err());
+
+    assert!(error.contains("nested deeper than"), "{}", error);
+}
+
+#[test]
+fn deeply_nested_texts_are_rejected() {
+    // flags and the ArgumentFormat history type, whose source format is the next text
+    let bytes = [0, 0, 0, 0, 3].repeat(100);
+    let error = format!("{:#}", TextProperty::read(&mut Reader::new(bytes, ObjectPadding::Four)).unwrap_err());
+
+    assert!(error.contains("nested deeper than"), "{}", error);
+}
+
+// read tables know their names, writing one of them reuses its index instead of appending it again
+#[test]
+fn read_name_tables_reuse_their_names() {
+    let mut read = read_sav(write_sav(&sample_archive(), &Compressor::None)).get_archive().unwrap();
+    let name_table = &mut read.content.name_table;
+    let names = name_table.list.len();
+    let index = name_table.list.iter().position(|name| name == "Level").unwrap();
+
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+    name_table.write_name(&mut writer, &FName::from("Level")).unwrap();
+
+    assert_eq!(name_table.list.len(), names);
+    assert_eq!(writer.into_inner(), (index as u16).to_le_bytes());
+}
+
+#[test]
+fn dynamic_struct_components_end_with_padding() {
+    let fields = || vec![serde_json::from_value::<Property>(property("Count", "IntProperty", json!({ "Int32": 3 }))).unwrap()];
+    let mut name_table = NameTable::new(Vec::new());
+
+    let mut value = Writer::new(Vec::new(), ObjectPadding::Four);
+    DynamicStruct { properties: fields() }.write(&mut value, &mut name_table).unwrap();
+
+    let mut component = Writer::new(Vec::new(), ObjectPadding::Four);
+    DynamicStructComponent { properties: fields() }.write(&mut component, &mut name_table).unwrap();
+
+    let value = value.into_inner();
+    let component = component.into_inner();
+
+    // both end at the None terminator, only the component is followed by a u64
+    assert_eq!(&component[..value.len()], value.as_slice());
+    assert_eq!(&component[value.len()..], &[0; 8]);
+}
+
+#[test]
+fn dynamic_structs_round_trip_as_values_and_components() {
+    let mut archive = sample_archive();
+    let object = &mut archive.content.object_index[0];
+
+    object.properties.insert(0, serde_json::from_value(property("Stats", "StructProperty", json!({ "Struct": {
+        "struct_name": { "value": "PlayerStats" },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { "Dynamic": { "properties": [property("Kills", "IntProperty", json!({ "Int32": 12 }))] } }
+    } }))).unwrap());
+
+    object.components = serde_json::from_value(json!([{
+        "component_key": "Stats",
+        "component_type": { "DynamicStruct": {
+            "properties": [property("Deaths", "IntProperty", json!({ "Int32": 4 }))]
+        } }
+    }])).unwrap();
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().expect("read archive");
+
+    // the value holds the tag and body of one int property and the None terminator, nothing after it
+    assert_eq!(read.root().unwrap().get("Stats").unwrap().size, (2 + 2 + 4 + 4) + (1 + 4) + 2);
+    assert!(read.semantic_eq(&archive).unwrap());
+
+    round_trip("dynamic structs", bytes);
+}
+
+#[test]
+fn map_elements_keep_their_order() {
+    let keys = [5, 1, 3];
+
+    let mut archive = sample_archive();
+    let ranks = serde_json::from_value(property("Ranks", "MapProperty", json!({ "Map": {
+        "key_type": { "value": "IntProperty" },
+        "value_type": { "value": "StrProperty" },
+        "elements": keys.iter().map(|key| json!([{ "Int32": key }, { "Str": format!("rank {}", key) }])).collect::<Vec<_>>()
+    } }))).unwrap();
+
+    archive.content.object_index[0].properties.push(ranks);
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let content = read_sav(bytes.clone()).get_content().unwrap();
+
+    // the keys are written in the order of the elements, not sorted
+    let first_entry: &[u8] = &[0x03, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00];
+    assert!(content.windows(first_entry.len()).any(|window| window == first_entry));
+
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+    let Some(PropertyData::Map(ranks)) = read.root().unwrap().get_path("Ranks").unwrap() else {
+        panic!("expected a map");
+    };
+
+    let read_keys = ranks.elements.iter()
+        .map(|(key, _)| match key {
+            PropertyData::Int32(key) => *key,
+            _ => panic!("expected an int key, got {:?}", key),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(read_keys, keys);
+    round_trip("map order", bytes);
+}
+
+// only map keys use the alt struct parser, struct array elements are read as full structs
+#[test]
+fn struct_keys_use_the_alt_parser_only_in_maps() {
+    let mut archive = sample_archive();
+    let guids = serde_json::from_value(property("Guids", "ArrayProperty", json!({ "Array": {
+        "inner_type": { "value": "StructProperty" },
+        "head_data": { "Struct": {
+            "name": { "value": "Guids" },
+            "type_name": { "value": "StructProperty" },
+            "index": 0,
+            "struct_name": { "value": "Guid" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 }
+        } },
+        "elements": [{ "Struct": {
+            "struct_name": { "value": "Guid" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+            "data": { "Guid": { "a": 5, "b": 6, "c": 7, "d": 8 } }
+        } }]
+    } }))).unwrap();
+
+    archive.content.object_index[0].properties.push(guids);
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+    let root = read.root().unwrap();
+
+    let Some(PropertyData::Map(keys)) = root.get_path("PersistenceKeys").unwrap() else {
+        panic!("expected a map");
+    };
+    assert!(matches!(keys.elements[0], (PropertyData::StructReference(_), PropertyData::Struct(_))));
+
+    let Some(PropertyData::Array(guids)) = root.get_path("Guids").unwrap() else {
+        panic!("expected an array");
+    };
+    assert!(matches!(&guids.elements[0], PropertyData::Struct(element) if matches!(element.data, StructData::Guid(_))));
+
+    round_trip("struct keys", bytes);
+}
+
+#[test]
+fn verified_writes_reject_saves_that_read_back_differently() {
+    let mut archive = sample_archive();
+    archive.root_mut().unwrap().set_int("Level", 21).unwrap();
+
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+    SavFile::write_verified(&mut writer, &archive, &Compressor::Zlib).expect("a plain edit verifies");
+
+    let read = read_sav(writer.into_inner()).get_archive().unwrap();
+    assert_eq!(read.root().unwrap().get_int("Level"), Some(21));
+
+    // raw bytes are written as they are, but read back as the int they hold
+    archive.root_mut().unwrap().get_mut("Level").unwrap().data = PropertyData::Raw(vec![0, 21, 0, 0, 0]);
+
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+    writer.preserve_name_table = true;
+
+    let err = SavFile::write_verified(&mut writer, &archive, &Compressor::Zlib).unwrap_err();
+
+    assert!(err.to_string().contains("does not match"), "{}", err);
+    assert!(writer.into_inner().is_empty());
+}
+
+#[test]
+fn last_saved_round_trips() {
+    let mut archive = sample_archive();
+
+    // profile saves have no timestamp
+    assert!(archive.last_saved().is_none());
+    assert!(archive.set_last_saved(chrono::Utc::now()).is_err());
+
+    archive.content.object_index[0].properties.push(serde_json::from_value(property("SaveDateTime", "StructProperty", json!({ "Struct": {
+        "struct_name": { "value": "DateTime" },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { "DateTime": "2023-07-25T12:34:56.1234567Z" }
+    } }))).unwrap());
+
+    let saved = "2024-01-02T03:04:05.5Z".parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+    archive.set_last_saved(saved).unwrap();
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    assert_eq!(read.last_saved().unwrap().to_chrono(), Some(saved));
+    round_trip("last saved", bytes);
+}
+
+#[test]
+fn name_keyed_maps_iterate_as_typed_entries() {
+    let mut archive = sample_archive();
+    let stats = serde_json::from_value(property("Stats", "MapProperty", json!({ "Map": {
+        "key_type": { "value": "NameProperty" },
+        "value_type": { "value": "IntProperty" },
+        "elements": [
+            [{ "Name": { "value": "Kills" } }, { "Int32": 12 }],
+            [{ "Name": { "value": "Deaths" } }, { "Int32": 4 }]
+        ]
+    } }))).unwrap();
+
+    archive.content.object_index[0].properties.push(stats);
+
+    let read = read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap();
+    let Some(PropertyData::Map(stats)) = read.root().unwrap().get_path("Stats").unwrap() else {
+        panic!("expected a map");
+    };
+
+    let entries = stats.name_entries()
+        .map(|entry| entry.map(|(key, value)| (key.value.as_str(), value)))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+
+    assert!(matches!(entries.as_slice(), [("Kills", PropertyData::Int32(12)), ("Deaths", PropertyData::Int32(4))]));
+
+    // the keys are names, so reading them as ints fails
+    assert!(stats.int_entries().all(|entry| entry.is_err()));
+}
+
+// oodle can't be written, so the chunks hold uncompressed Kraken blocks: a two byte header and the bytes as they are
+#[cfg(feature = "oodle")]
+#[test]
+fn oodle_chunks_are_decompressed() {
+    let mut sav_file = read_sav(write_sav(&sample_archive(), &Compressor::None));
+    let content = sav_file.get_content().unwrap();
+
+    for chunk in &mut sav_file.chunks {
+        chunk.compressed_data.splice(0..0, [0x4C, 0x06]);
+        chunk.compression_info.compressed_size += 2;
+        chunk.compressor = Compressor::Oodle;
+    }
+
+    assert_eq!(sav_file.get_content().unwrap(), content);
+
+    let uncompressed_size = sav_file.chunks[0].compression_info.uncompressed_size;
+
+    sav_file.chunks[0].compression_info.uncompressed_size = u64::MAX;
+
+    assert!(sav_file.get_content().unwrap_err().to_string().starts_with("Oodle chunk is too large"));
+
+    sav_file.chunks[0].compression_info.uncompressed_size = uncompressed_size;
+    sav_file.chunks[0].compressed_data.truncate(16);
+
+    assert!(sav_file.get_content().is_err());
+}
+
+#[test]
+fn global_variables_are_keyed_by_name() {
+    let mut archive = sample_archive();
+
+    archive.content.object_index[0].components = serde_json::from_value(json!([{
+        "component_key": "GlobalVariables",
+        "component_type": { "GlobalVariables": {
+            "name": { "value": "GlobalVariables" },
+            "variables": [
+                { "name": { "value": "Quest_Started_X" }, "value": { "Bool": true } },
+                { "name": { "value": "Quest_Completed_X" }, "value": { "Bool": false } }
+            ]
+        } }
+    }])).unwrap();
+
+    let mut read = read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap();
+    let ComponentType::GlobalVariables(variables) = &mut read.content.object_index[0].components.as_mut().unwrap()[0].component_type else {
+        panic!("expected global variables");
+    };
+
+    assert!(matches!(variables.get("Quest_Completed_X"), Some(VariableValue::Bool(false))));
+    assert!(variables.get("Quest_Missing").is_none());
+
+    variables.set("Quest_Completed_X", VariableValue::Bool(true));
+    variables.set("Quest_Count_X", VariableValue::Int(3));
+
+    let names = variables.variables.iter().map(|variable| variable.name.value.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(names, ["Quest_Started_X", "Quest_Completed_X", "Quest_Count_X"]);
+    assert!(matches!(variables.get("Quest_Completed_X"), Some(VariableValue::Bool(true))));
+
+    round_trip("global variables", write_sav(&read, &Compressor::None));
+}
+
+#[test]
+fn variables_are_built_with_typed_setters() {
+    let mut archive = sample_archive();
+    let mut variables: Variables = serde_json::from_value(json!({ "name": { "value": "Variables" }, "variables": [] })).unwrap();
+
+    variables.set_bool("Quest_Completed_X", false).unwrap();
+    variables.set_int("Quest_Count_X", 3).unwrap();
+    variables.set_float("Quest_Progress_X", 0.5).unwrap();
+    variables.set_name("SelectedDye", "Dye_Crimson").unwrap();
+
+    // updating keeps the position of the variable
+    variables.set_bool("Quest_Completed_X", true).unwrap();
+
+    // changing the type of a variable has to go through set_coerced
+    assert!(variables.set_int("Quest_Completed_X", 1).is_err());
+    assert!(variables.set_name("Quest_Count_X", "Three").is_err());
+
+    archive.content.object_index[0].components = Some(vec![Component {
+        component_key: "Variables".to_owned(),
+        component_type: ComponentType::Variables(variables),
+    }]);
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    let ComponentType::Variables(variables) = &read.content.object_index[0].components.as_ref().unwrap()[0].component_type else {
+        panic!("expected variables");
+    };
+
+    let names = variables.variables.iter().map(|variable| variable.name.value.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(names, ["Quest_Completed_X", "Quest_Count_X", "Quest_Progress_X", "SelectedDye"]);
+    assert!(matches!(variables.get("Quest_Completed_X"), Some(VariableValue::Bool(true))));
+    assert!(matches!(variables.get("Quest_Count_X"), Some(VariableValue::Int(3))));
+    assert!(matches!(variables.get("Quest_Progress_X"), Some(VariableValue::Float(value)) if *value == 0.5));
+    assert!(matches!(variables.get("SelectedDye"), Some(VariableValue::Name(name)) if name.value == "Dye_Crimson"));
+
+    round_trip("variable setters", bytes);
+}
+
+#[test]
+fn variable_setters_keep_their_type() {
+    let mut flag = Variable::new("Quest_Completed_X", VariableValue::Bool(false));
+
+    flag.set_bool(true).unwrap();
+    assert!(flag.set_int(1).is_err());
+    assert!(matches!(flag.value, VariableValue::Bool(true)));
+
+    flag.set_coerced(VariableValue::Int(0)).unwrap();
+    assert!(matches!(flag.value, VariableValue::Bool(false)));
+    assert!(flag.set_coerced(VariableValue::Int(2)).is_err());
+
+    let mut count = Variable::new("Quest_Count_X", VariableValue::Int(1));
+
+    count.set_coerced(VariableValue::Float(4.0)).unwrap();
+    assert!(matches!(count.value, VariableValue::Int(4)));
+    assert!(count.set_coerced(VariableValue::Float(4.5)).is_err());
+    assert!(count.set_coerced(VariableValue::Name(FName::from("Four"))).is_err());
+}
+
+// name variables go through the name table, including numbered names
+#[test]
+fn name_variables_round_trip() {
+    let mut archive = sample_archive();
+
+    archive.content.object_index[0].components = serde_json::from_value(json!([{
+        "component_key": "Variables",
+        "component_type": { "Variables": {
+            "name": { "value": "Variables" },
+            "variables": [
+                { "name": { "value": "SelectedDye" }, "value": { "Name": { "value": "Dye_Crimson" } } },
+                { "name": { "value": "SelectedTrinket" }, "value": { "Name": { "value": "Trinket", "number": 3 } } }
+            ]
+        } }
+    }])).unwrap();
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    let ComponentType::Variables(variables) = &read.content.object_index[0].components.as_ref().unwrap()[0].component_type else {
+        panic!("expected variables");
+    };
+
+    assert!(matches!(variables.get("SelectedDye"), Some(VariableValue::Name(name)) if name.value == "Dye_Crimson"));
+    assert!(matches!(variables.get("SelectedTrinket"), Some(VariableValue::Name(name)) if name.to_string() == "Trinket_3"));
+
+    round_trip("name variables", bytes);
+}
+
+#[test]
+fn summary_counts_nested_archives() {
+    let summary = world_archive().summary();
+
+    assert_eq!(summary.archives, 4);
+    assert_eq!(summary.objects, 4);
+    assert_eq!(summary.actors, 3);
+    assert_eq!(summary.properties, 4);
+    assert!(summary.components.is_empty());
+
+    let mut archive = sample_archive();
+
+    archive.content.object_index[0].components = serde_json::from_value(json!([
+        { "component_key": "GlobalVariables", "component_type": { "GlobalVariables": { "name": { "value": "GlobalVariables" }, "variables": [] } } },
+        { "component_key": "Inventory", "component_type": { "DynamicStruct": { "properties": [] } } }
+    ])).unwrap();
+
+    let summary = read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap().summary();
+
+    assert_eq!(summary.objects, 2);
+    assert!(summary.names > 0);
+    assert_eq!(summary.components.get("GlobalVariables"), Some(&1));
+    assert_eq!(summary.components.get("DynamicStruct"), Some(&1));
+}
+
+// the fixture's table is in usage order, reversing it makes preserving observable
+#[test]
+fn preserved_name_table_order_round_trips() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/synthetic_profile.sav");
+    let mut archive = read_sav(fs::read(path).unwrap()).get_archive().unwrap();
+
+    archive.content.name_table.list.reverse();
+
+    let write_preserved = |archive: &SaveGameArchive| {
+        let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+        writer.preserve_name_table = true;
+        SavFile::write(&mut writer, archive, &Compressor::None).unwrap();
+
+        writer.into_inner()
+    };
+
+    let bytes = write_preserved(&archive);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    assert_eq!(read.content.name_table.list, archive.content.name_table.list);
+    assert!(read.semantic_eq(&archive).unwrap());
+    assert_content_eq(
+        "preserved name table",
+        &read_sav(bytes).get_content().unwrap(),
+        &read_sav(write_preserved(&read)).get_content().unwrap(),
+    );
+}
+
+// a padding mismatch in a nested archive would show up as unread (trailing) bytes
+#[test]
+fn nested_archives_are_read_completely() {
+    let read = read_sav(write_sav(&world_archive(), &Compressor::None)).get_archive().unwrap();
+    let archives = read.content.archives();
+
+    assert_eq!(archives.len(), 4);
+
+    for archive in archives {
+        for object in &archive.object_index {
+            assert!(object.trailing_data.is_empty(), "{} has unread bytes", object.object_path);
+        }
+    }
+}
+
+#[test]
+fn raw_blobs_are_kept_as_bytes() {
+    let bytes = write_sav(&world_archive(), &Compressor::None);
+    let content = read_sav(bytes.clone()).get_content().unwrap();
+
+    let mut reader = Reader::new(content, ObjectPadding::Four);
+
+    reader.raw_blobs = true;
+
+    let archive = SaveGameArchive::read(&mut reader).unwrap();
+
+    assert!(matches!(
+        archive.root().unwrap().get_struct("PersistenceData").unwrap().data,
+        StructData::RawBlob(ref data) if !data.is_empty()
+    ));
+    assert!(archive.content.actors().is_empty());
+
+    assert_content_eq(
+        "raw blobs",
+        &read_sav(bytes).get_content().unwrap(),
+        &read_sav(write_sav(&archive, &Compressor::None)).get_content().unwrap(),
+    );
+}
+
+// blobs of unknown save game classes are kept as bytes when they aren't archives, or read as registered
+#[test]
+fn unknown_blob_kinds_fall_back_to_raw_bytes() {
+    let mut world = serde_json::to_value(world_archive()).unwrap();
+    *world.pointer_mut("/content/save_game_class_path/path").unwrap() = json!("/Game/Mods/BP_ModSaveGame");
+    *world.pointer_mut("/content/object_index/0/object_path").unwrap() = json!("/Game/Mods/BP_ModSaveGame");
+
+    let archive: SaveGameArchive = serde_json::from_value(world).unwrap();
+    let bytes = write_sav(&archive, &Compressor::None);
+    let content = read_sav(bytes.clone()).get_content().unwrap();
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    assert!(matches!(
+        read.root().unwrap().get_struct("PersistenceData").unwrap().data,
+        StructData::RawBlob(ref data) if !data.is_empty()
+    ));
+    assert_content_eq("raw fallback blob", &content, &read_sav(write_sav(&read, &Compressor::None)).get_content().unwrap());
+
+    let mut reader = Reader::new(content, ObjectPadding::Four);
+
+    reader.blob_kinds.push(("/Game/Mods/BP_ModSaveGame".to_owned(), PersistenceBlobKind::Container));
+
+    let read = SaveGameArchive::read(&mut reader).unwrap();
+
+    assert_eq!(container(&read).actors.len(), 3);
+    round_trip("registered blob kind", bytes);
+}
+
+// errors in nested archives report where they are in the outermost archive
+#[test]
+fn nested_error_offsets_are_absolute() {
+    let mut world = serde_json::to_value(world_archive()).unwrap();
+    let actor = "/content/object_index/0/properties/0/data/Struct/data/PersistenceContainer/actors/9/archive/object_index/0/properties";
+
+    *world.pointer_mut(actor).unwrap() = json!([property("Tag", "NameProperty", json!({ "Name": { "value": "Rusty" } }))]);
+
+    let archive: SaveGameArchive = serde_json::from_value(world).unwrap();
+    let mut content = read_sav(write_sav(&archive, &Compressor::None)).get_content().unwrap();
+    let mut reader = Reader::new(content.clone(), ObjectPadding::Four);
+
+    reader.record_offsets = true;
+
+    let read = SaveGameArchive::read(&mut reader).unwrap();
+    let offset = read.content.archives().into_iter()
+        .find(|nested| nested.object_index[0].object_path == "/Game/World_Base/Actors/Actor_9")
+        .and_then(|nested| nested.object_index[0].properties[0].offset)
+        .unwrap() as usize;
+
+    // the tag head, then the guid flag of the name property
+    content[offset + 13..offset + 15].copy_from_slice(&0x7FFFu16.to_le_bytes());
+
+    let error = SaveGameArchive::read(&mut Reader::new(content, ObjectPadding::Four)).unwrap_err();
+    let expected = format!("while parsing property Tag (NameProperty) at offset {:#X}", offset + 12);
+
+    assert!(error.chain().any(|cause| cause.to_string() == expected), "{:#}", error);
+}
+
+// offsets in nested archives are relative to the outermost archive as well
+#[test]
+fn recorded_offsets_point_at_objects_and_properties() {
+    let content = read_sav(write_sav(&world_archive(), &Compressor::None)).get_content().unwrap();
+    let mut reader = Reader::new(content.clone(), ObjectPadding::Four);
+
+    reader.record_offsets = true;
+
+    let archive = SaveGameArchive::read(&mut reader).unwrap();
+    let mut checked = 0;
+
+    for nested in archive.content.archives() {
+        for object in &nested.object_index {
+            let mut reader = Reader::new(content.clone(), ObjectPadding::Four);
+
+            reader.seek(SeekFrom::Start(object.offset.unwrap())).unwrap();
+            assert!(reader.read_u32::<LittleEndian>().unwrap() > 0);
+
+            for property in &object.properties {
+                reader.seek(SeekFrom::Start(property.offset.unwrap())).unwrap();
+                assert_eq!(nested.read_name(&mut reader).unwrap(), property.name);
+
+                checked += 1;
+            }
+        }
+    }
+
+    assert_eq!(checked, 4);
+    assert!(serde_json::to_string(&archive).unwrap().contains("__offset"));
+    assert!(!serde_json::to_string(&world_archive()).unwrap().contains("__offset"));
+}
+
+#[test]
+fn unknown_save_classes_read_blobs_as_archives() {
+    let class_path = "/Game/_Core/Blueprints/Base/BP_RemnantSaveGameSeason";
+
+    let blob = json!({ "Struct": {
+        "struct_name": { "value": "PersistenceBlob" },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { "PersistenceBlob": { "archive": {
+            "package_version": { "ue4_version": 522, "ue5_version": 1008 },
+            "save_game_class_path": null,
+            "name_table": { "list": [] },
+            "object_index": [{
+                "object_id": 0,
+                "was_loaded": true,
+                "object_path": "/Game/Seasons/Season_1",
+                "loaded_data": null,
+                "properties": [property("Rank", "IntProperty", json!({ "Int32": 7 }))],
+                "components": null
+            }],
+            "version": 1
+        } } }
+    } });
+
+    let archive: SaveGameArchive = serde_json::from_value(json!({
+        "header": { "save_game_file_version": 9, "build_number": 400000 },
+        "content": {
+            "package_version": { "ue4_version": 522, "ue5_version": 1008 },
+            "save_game_class_path": { "path": class_path, "name": "BP_RemnantSaveGameSeason_C" },
+            "name_table": { "list": [] },
+            "object_index": [{
+                "object_id": 0,
+                "was_loaded": true,
+                "object_path": class_path,
+                "loaded_data": null,
+                "properties": [property("SeasonData", "StructProperty", blob)],
+                "components": null
+            }],
+            "version": 1
+        }
+    })).unwrap();
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    let StructData::PersistenceBlob(blob) = &read.root().unwrap().get_struct("SeasonData").unwrap().data else {
+        panic!("expected a persistence blob");
+    };
+
+    assert_eq!(blob.archive.object_index[0].get_int("Rank"), Some(7));
+
+    round_trip("unknown save class", bytes);
+}
+
+// a loaded object 0 takes its path from the class path, so the index doesn't repeat it
+#[test]
+fn top_level_object_path_comes_from_the_class_path() {
+    let class_path = "/Game/_Core/Blueprints/Base/BP_RemnantSaveGameProfile";
+    let mut archive = sample_archive();
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let content = read_sav(bytes.clone()).get_content().unwrap();
+
+    let mut fstring = ((class_path.len() + 1) as u32).to_le_bytes().to_vec();
+    fstring.extend(class_path.bytes().chain([0]));
+
+    assert_eq!(content.windows(fstring.len()).filter(|window| *window == fstring).count(), 1);
+    assert_eq!(read_sav(bytes.clone()).get_archive().unwrap().root().unwrap().object_path, class_path);
+
+    round_trip("top level object", bytes);
+
+    archive.content.object_index[0].object_path = "/Game/Other".to_owned();
+
+    let error = SavFile::write(&mut Writer::new(Vec::new(), ObjectPadding::Four), &archive, &Compressor::None).unwrap_err();
+    assert!(format!("{:#}", error).contains("Object 0 path /Game/Other does not match the save game class path"), "{:#}", error);
+}
+
+#[test]
+fn objects_are_found_by_path() {
+    let mut archive = sample_archive();
+
+    assert_eq!(archive.find_object("Quest_Global").unwrap().object_id, 1);
+    assert!(archive.find_object("Character").is_none());
+    assert_eq!(archive.objects_matching(|object| !object.properties.is_empty()).count(), 1);
+
+    archive.find_object_mut("BP_RemnantSaveGameProfile").unwrap().set_int("Level", 21).unwrap();
+
+    assert_eq!(archive.root().unwrap().get_int("Level"), Some(21));
+}
+
+// objects without properties only skip the property list when they are completely empty
+// bytes after an object's properties are kept, whether zeroed padding or not
+#[test]
+fn object_trailing_bytes_are_kept() {
+    let mut archive = sample_archive();
+
+    archive.content.object_index[0].trailing_data = vec![0; 4];
+    archive.content.object_index[1].trailing_data = vec![7, 0, 0, 0, 0, 0, 0, 0];
+    archive.content.object_index[1].properties.push(
+        serde_json::from_value(property("Stage", "IntProperty", json!({ "Int32": 7 }))).unwrap(),
+    );
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    assert_eq!(read.content.object_index[0].trailing_data, [0; 4]);
+    assert_eq!(read.content.object_index[1].trailing_data, [7, 0, 0, 0, 0, 0, 0, 0]);
+    assert!(read.semantic_eq(&archive).unwrap());
+
+    round_trip("trailing bytes", bytes);
+}
+
+#[test]
+fn objects_without_properties_round_trip() {
+    let mut archive = sample_archive();
+    let objects = [
+        json!({ "properties": [], "trailing_data": [1, 2, 3, 4] }),
+        json!({ "properties": [], "empty_property_list": true }),
+        json!({ "properties": [] }),
+        json!({ "properties": [property("Level", "IntProperty", json!({ "Int32": 5 }))] }),
+    ];
+
+    for (i, object) in objects.iter().enumerate() {
+        let mut value = json!({
+            "object_id": i + 1,
+            "was_loaded": false,
+            "object_path": format!("/Game/World_Base/Quests/Quest_{}", i),
+            "loaded_data": { "name": { "value": "Quest_C", "number": i }, "outer_id": 0 },
+            "components": null
+        });
+
+        value.as_object_mut().unwrap().extend(object.as_object().unwrap().clone());
+        archive.content.object_index.push(serde_json::from_value(value).unwrap());
+    }
+
+    archive.content.object_index.remove(1);
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+    let objects = &read.content.object_index;
+
+    assert_eq!(objects[1].trailing_data, [1, 2, 3, 4]);
+    assert!(objects[1].empty_property_list);
+    assert!(objects[2].empty_property_list);
+    assert!(!objects[3].empty_property_list);
+    assert_eq!(objects[4].get_int("Level"), Some(5));
+
+    round_trip("objects without properties", bytes);
+}
+
+#[test]
+fn content_delta_finds_changed_bytes() {
+    let mut archive = sample_archive();
+    let backup = read_sav(write_sav(&archive, &Compressor::Zlib));
+
+    assert!(backup.content_delta(&read_sav(write_sav(&archive, &Compressor::LZ4))).unwrap().is_empty());
+
+    archive.root_mut().unwrap().set_int("Level", 21).unwrap();
+
+    let current = read_sav(write_sav(&archive, &Compressor::Zlib));
+    let delta = backup.content_delta(&current).unwrap();
+
+    // the checksum and the level's low byte
+    assert_eq!(delta.len(), 2);
+    assert!(delta[0].end <= 4);
+    assert_eq!(delta[1].len(), 1);
+    assert_eq!(backup.get_content().unwrap()[delta[1].start], 20);
+    assert_eq!(current.get_content().unwrap()[delta[1].start], 21);
+}
+
+#[test]
+fn unchanged_chunks_are_reused() {
+    let mut archive = sample_archive();
+
+    // enough trailing data after the root properties for three chunks
+    archive.content.object_index[0].trailing_data = (0..300_000u32).map(|i| (i * 7 % 251) as u8).collect();
+
+    let mut previous = read_sav(write_sav(&archive, &Compressor::Zlib));
+    let boundaries = previous.chunk_boundaries();
+
+    // re-encoded at another level, so re-used chunks can be told apart from recompressed ones
+    for chunk in &mut previous.chunks {
+        let mut data = Vec::new();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+
+        ZlibDecoder::new(chunk.compressed_data.as_slice()).read_to_end(&mut data).unwrap();
+        encoder.write_all(&data).unwrap();
+
+        chunk.compressed_data = encoder.finish().unwrap();
+        chunk.compression_info.compressed_size = chunk.compressed_data.len() as u64;
+    }
+
+    assert_eq!(boundaries.len(), 3);
+    assert_eq!(boundaries[0], 8..8 + (2 << 16));
+    assert_eq!(boundaries[2].end, previous.get_content().unwrap().len());
+
+    archive.content.object_index[0].set_int("Level", 21).unwrap();
+
+    let mut writer = Writer::new(Vec::new(), ObjectPadding::Four);
+
+    SavFile::write_reusing(&mut writer, &archive, &previous).unwrap();
+
+    let bytes = writer.into_inner();
+
+    SavFile::verify(&bytes).unwrap();
+
+    let written = read_sav(bytes);
+    let delta = written.content_delta(&previous).unwrap();
+
+    assert!(delta.iter().all(|range| range.end <= boundaries[0].end));
+    assert_ne!(written.chunks[0].compressed_data, previous.chunks[0].compressed_data);
+    assert_eq!(written.chunks[1].compressed_data, previous.chunks[1].compressed_data);
+    assert_eq!(written.chunks[2].compressed_data, previous.chunks[2].compressed_data);
+    assert_eq!(written.get_archive().unwrap().content.object_index[0].get_int("Level"), Some(21));
+}
+
+#[test]
+fn loadout_lists_equipped_items_by_slot() {
+    let mut archive = sample_archive();
+    let item = |item_bp: i32, slot: i32| json!({ "Struct": {
+        "struct_name": { "value": "InventoryItemData" },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { "Dynamic": { "properties": [
+            property("ItemBP", "ObjectProperty", json!({ "Object": item_bp })),
+            property("EquipmentSlotIndex", "IntProperty", json!({ "Int32": slot }))
+        ] } }
+    } });
+
+    // an equipped item, an unequipped one, an empty slot and another equipped item
+    let items = json!({ "Array": {
+        "inner_type": { "value": "StructProperty" },
+        "head_data": { "Struct": {
+            "name": { "value": "Items" },
+            "type_name": { "value": "StructProperty" },
+            "index": 0,
+            "struct_name": { "value": "InventoryItemData" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 }
+        } },
+        "elements": [item(2, 0), item(3, -1), item(-1, 1), item(3, 2)]
+    } });
+
+    archive.content.object_index[1].properties.push(serde_json::from_value(property("Items", "ArrayProperty", items)).unwrap());
+
+    for (object_id, name) in [(2, "Weapon_Repeater_C"), (3, "Relic_Dragonheart_C")] {
+        archive.content.object_index.push(serde_json::from_value(json!({
+            "object_id": object_id,
+            "was_loaded": false,
+            "object_path": format!("/Game/World_Base/Items/{}.{}", name.trim_end_matches("_C"), name),
+            "loaded_data": { "name": { "value": name }, "outer_id": 0 },
+            "properties": [],
+            "components": null
+        })).unwrap());
+    }
+
+    let read = read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap();
+    let loadout = Loadout::read(&read).unwrap();
+
+    let slots = loadout.slots.iter().map(|(slot, item)| (*slot, item.value.as_str())).collect::<Vec<_>>();
+
+    assert_eq!(slots, [(0, "Weapon_Repeater_C"), (2, "Relic_Dragonheart_C")]);
+    assert!(Loadout::read(&sample_archive()).unwrap().slots.is_empty());
+}
+
+#[test]
+fn inventory_item_views_read_and_edit_item_structs() {
+    let mut archive = sample_archive();
+    let item = |properties: Vec<Value>| json!({ "Struct": {
+        "struct_name": { "value": "InventoryItemData" },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { "Dynamic": { "properties": properties } }
+    } });
+
+    let items = json!({ "Array": {
+        "inner_type": { "value": "StructProperty" },
+        "head_data": { "Struct": {
+            "name": { "value": "Items" },
+            "type_name": { "value": "StructProperty" },
+            "index": 0,
+            "struct_name": { "value": "InventoryItemData" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 }
+        } },
+        "elements": [
+            item(vec![
+                property("ItemBP", "ObjectProperty", json!({ "Object": 1 })),
+                property("Quantity", "IntProperty", json!({ "Int32": 3 })),
+                property("Level", "IntProperty", json!({ "Int32": 2 }))
+            ]),
+            // no blueprint, so not an inventory item
+            item(vec![property("Quantity", "IntProperty", json!({ "Int32": 1 }))])
+        ]
+    } });
+
+    archive.content.object_index[0].properties.push(serde_json::from_value(property("Items", "ArrayProperty", items)).unwrap());
+
+    let items = InventoryItem::read_all(archive.root().unwrap());
+
+    assert_eq!(items, [InventoryItem { item_bp: 1, quantity: Some(3), level: Some(2), equipment_slot: None }]);
+
+    let Some(PropertyData::Array(elements)) = archive.root_mut().unwrap().get_path_mut("Items").unwrap() else {
+        panic!("expected an array");
+    };
+    let [PropertyData::Struct(first), PropertyData::Struct(second)] = elements.elements.as_mut_slice() else {
+        panic!("expected two structs");
+    };
+    let (StructData::Dynamic(first), StructData::Dynamic(second)) = (&mut first.data, &mut second.data) else {
+        panic!("expected dynamic structs");
+    };
+
+    assert!(InventoryItem::from_struct(second).is_none());
+
+    // fields the struct doesn't have can't be added by the view
+    let mut item = items[0].clone();
+
+    item.equipment_slot = Some(0);
+    assert!(item.apply(first).is_err());
+
+    item.equipment_slot = None;
+    item.level = Some(10);
+    item.apply(first).unwrap();
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    assert_eq!(InventoryItem::read_all(read.root().unwrap())[0].level, Some(10));
+    round_trip("inventory item", bytes);
+}
+
+#[test]
+fn inventory_items_are_resolved_and_applied() {
+    let mut archive = sample_archive();
+    let item = |item_bp: i32, quantity: i32| json!({ "Struct": {
+        "struct_name": { "value": "InventoryItemData" },
+        "guid": { "a": 0, "b": 0, "c": 0, "d": 0 },
+        "data": { "Dynamic": { "properties": [
+            property("ItemBP", "ObjectProperty", json!({ "Object": item_bp })),
+            property("Quantity", "IntProperty", json!({ "Int32": quantity }))
+        ] } }
+    } });
+
+    let items = json!({ "Array": {
+        "inner_type": { "value": "StructProperty" },
+        "head_data": { "Struct": {
+            "name": { "value": "Items" },
+            "type_name": { "value": "StructProperty" },
+            "index": 0,
+            "struct_name": { "value": "InventoryItemData" },
+            "guid": { "a": 0, "b": 0, "c": 0, "d": 0 }
+        } },
+        "elements": [item(2, 3), item(-1, 1), item(2, 5)]
+    } });
+
+    archive.content.object_index[1].properties.push(serde_json::from_value(property("Items", "ArrayProperty", items)).unwrap());
+    archive.content.object_index.push(serde_json::from_value(json!({
+        "object_id": 2,
+        "was_loaded": false,
+        "object_path": "/Game/World_Base/Items/Relic_Consumable_Dragonheart.Relic_Consumable_Dragonheart_C",
+        "loaded_data": { "name": { "value": "Relic_Consumable_Dragonheart_C" }, "outer_id": 0 },
+        "properties": [],
+        "components": null
+    })).unwrap());
+
+    let mut archive = read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap();
+    let mut inventory = Inventory::read(&archive);
+
+    // the item without a blueprint is left out
+    assert_eq!(inventory.items().len(), 2);
+    assert_eq!(inventory.items()[0].id, FName::from("Relic_Consumable_Dragonheart_C"));
+    assert_eq!(inventory.items()[1].item.quantity, Some(5));
+    assert_eq!(inventory.find("Relic_Consumable_Dragonheart_C").count(), 2);
+
+    assert_eq!(inventory.set_quantity("Relic_Consumable_Dragonheart_C", 9), 2);
+    assert_eq!(inventory.set_quantity("Relic_Consumable_Unknown_C", 9), 0);
+
+    inventory.apply(&mut archive).unwrap();
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+    let quantities = Inventory::read(&read).items().iter().map(|entry| entry.item.quantity).collect::<Vec<_>>();
+
+    assert_eq!(quantities, [Some(9), Some(9)]);
+    assert!(matches!(read.content.object_index[1].get_path("Items[1].Quantity").unwrap(), Some(PropertyData::Int32(1))));
+
+    round_trip("inventory", bytes);
+}
+
+#[test]
+fn character_names_are_renamed_as_str_or_name() {
+    let mut archive = sample_archive();
+
+    assert_eq!(archive.character_name().unwrap(), "Странник 旅人");
+
+    archive.set_character_name("Путник").unwrap();
+
+    let bytes = write_sav(&archive, &Compressor::None);
+    let read = read_sav(bytes.clone()).get_archive().unwrap();
+
+    assert_eq!(read.character_name().unwrap(), "Путник");
+    round_trip("character name", bytes);
+
+    // some saves store the name as a NameProperty
+    let root = archive.root_mut().unwrap();
+    root.get_mut("CharacterName").unwrap().data = PropertyData::Name(FName::from("Wanderer"));
+
+    archive.set_character_name("Traveler").unwrap();
+    assert!(matches!(&archive.root().unwrap().get("CharacterName").unwrap().data, PropertyData::Name(name) if name.value == "Traveler"));
+
+    archive.root_mut().unwrap().properties.retain(|property| property.name.value != "CharacterName");
+
+    assert_eq!(archive.character_name().unwrap_err().to_string(), "Save has no CharacterName property");
+    assert!(archive.set_character_name("Traveler").is_err());
+}
+
+#[test]
+fn interior_nuls_are_reported_by_string() {
+    for name in ["Wander\0er", "Стран\0ник"] {
+        let mut archive = sample_archive();
+
+        archive.set_character_name(name).unwrap();
+
+        let error = format!("{:#}", SavFile::write(&mut Writer::new(Vec::new(), ObjectPadding::Four), &archive, &Compressor::None).unwrap_err());
+
+        assert!(error.contains(&format!("FString {:?} contains an interior NUL", name)), "{}", error);
+    }
+}
+
+// UTF-16 strings take two bytes per code unit and empty strings have no terminator
+#[test]
+fn string_sizes_count_written_bytes() {
+    let mut archive = sample_archive();
+    let root = archive.root_mut().unwrap();
+
+    root.properties.push(serde_json::from_value(property("Title", "StrProperty", json!({ "Str": "" }))).unwrap());
+    root.properties.push(serde_json::from_value(property("Portrait", "SoftObjectProperty", json!({ "SoftObject": "/Game/Портрет" }))).unwrap());
+
+    let read = read_sav(write_sav(&archive, &Compressor::None)).get_archive().unwrap();
+    let root = read.root().unwrap();
+
+    assert_eq!(root.get("CharacterName").unwrap().size, 4 + ("Странник 旅人".chars().count() as u32 + 1) * 2);
+    assert_eq!(root.get("Title").unwrap().size, 4);
+    assert_eq!(root.get("Portrait").unwrap().size, 4 + ("/Game/Портрет".chars().count() as u32 + 1) * 2);
+}
+
+#[test]
+fn oversized_chunks_are_rejected() {
+    let bytes = write_sav(&sample_archive(), &Compressor::LZ4);
+
+    // the first chunk's compression info follows the sav header, package tag, block size and compressor
+    let info = 12 + 8 + 8 + 1;
+
+    let mut huge_uncompressed = bytes.clone();
+    huge_uncompressed[info + 8..info + 16].copy_from_slice(&(1u64 << 40).to_le_bytes());
+
+    let err = SavFile::read(&mut Reader::new(huge_uncompressed, ObjectPadding::Four)).unwrap_err();
+    assert!(err.to_string().contains("too large"), "{}", err);
+
+    let mut huge_compressed = bytes;
+    huge_compressed[info..info + 8].copy_from_slice(&(1u64 << 40).to_le_bytes());
+
+    let err = SavFile::read(&mut Reader::new(huge_compressed, ObjectPadding::Four)).unwrap_err();
+    assert!(err.to_string().contains("past the end of the data"), "{}", err);
+}
+
+#[test]
+fn floats_keep_their_bits() {
+    let floats = [0.1f32, -0.0, f32::MIN_POSITIVE / 3.0, 16_777_217.0, f32::MAX];
+    let doubles = [0.1f64, -0.0, f64::MIN_POSITIVE / 3.0, 1.0 / 3.0, f64::MAX];
+
+    let mut archive = sample_archive();
+
+    for (i, (float, double)) in floats.iter().zip(doubles).enumerate() {
+        let properties = &mut archive.content.object_index[0].properties;
+
+        properties.push(serde_json::from_value(property(&format!("Float{}", i), "FloatProperty", json!({ "Float": float }))).unwrap());
+        properties.push(serde_json::from_value(property(&format!("Double{}", i), "DoubleProperty", json!({ "Double": double }))).unwrap());
+    }
+
+    // unedited values survive both the binary and the JSON form
+    let bytes = write_sav(&archive, &Compressor::None);
+    let json = serde_json::to_string(&read_sav(bytes.clone()).get_archive().unwrap()).unwrap();
+    let read: SaveGameArchive = serde_json::from_str(&json).unwrap();
+
+    for (i, (float, double)) in floats.iter().zip(doubles).enumerate() {
+        let root = read.root().unwrap();
+
+        assert!(matches!(root.get(&format!("Float{}", i)).unwrap().data, PropertyData::Float(value) if value.to_bits() == float.to_bits()));
+        assert!(matches!(root.get(&format!("Double{}", i)).unwrap().data, PropertyData::Double(value) if value.to_bits() == double.to_bits()));
+    }
+
+    assert_content_eq("floats", &read_sav(bytes).get_content().unwrap(), &read_sav(write_sav(&read, &Compressor::None)).get_content().unwrap());
+
+    // just above the midpoint of 1.0 and the next f32, going through f64 first would round to the midpoint and then down
+    let mut value = PropertyData::Float(0.0);
+
+    value.set_decimal(" 1.0000000596046447755 ").unwrap();
+    assert!(matches!(value, PropertyData::Float(value) if value.to_bits() == 0x3F80_0001));
+
+    assert!(PropertyData::Int32(0).set_decimal("1.5").is_err());
+}
+
+// sizes in the sample are all 0, the writer has to recompute them
+#[test]
+fn setters_recompute_sizes() {
+    let mut archive = sample_archive();
+    let root = archive.root_mut().unwrap();
+
+    root.set_int("Level", 21).unwrap();
+    root.set_str("CharacterName", "Wanderer of the Labyrinth").unwrap();
+
+    assert!(root.set_int("CharacterName", 1).is_err());
+    assert!(root.set_int("Missing", 1).is_err());
+
+    let read = read_sav(write_sav(&archive, &Compressor::Zlib)).get_archive().expect("read archive");
+    let root = read.root().unwrap();
+
+    assert_eq!(root.get_int("Level"), Some(21));
+    assert_eq!(root.get_str("CharacterName"), Some("Wanderer of the Labyrinth"));
+    assert_eq!(root.get("Level").unwrap().size, 4);
+    assert_eq!(root.get("CharacterName").unwrap().size, 4 + 26);
+}
+
+// both the sav file and its decompressed content can be read straight from disk
+#[test]
+fn sample_archive_reads_from_file() {
+    let archive = sample_archive();
+    let dir = std::env::temp_dir().join(format!("remnant-save-parser-{}", std::process::id()));
+
+    fs::create_dir_all(&dir).unwrap();
+
+    let sav_path = dir.join("sample.sav");
+    let content_path = dir.join("sample.bin");
+
+    fs::write(&sav_path, write_sav(&archive, &Compressor::Zlib)).unwrap();
+
+    let mut reader = Reader::from_stream(BufReader::new(File::open(&sav_path).unwrap()), ObjectPadding::Four).unwrap();
+    let sav_file = SavFile::read(&mut reader).expect("read sav file from disk");
+
+    fs::write(&content_path, sav_file.get_content().unwrap()).unwrap();
+
+    let mut reader = Reader::from_stream(BufReader::new(File::open(&content_path).unwrap()), ObjectPadding::Four).unwrap();
+    let read = SaveGameArchive::read(&mut reader).expect("read archive from disk");
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(read.semantic_eq(&archive).unwrap());
+}
+
+// the uesave layout has to hold everything a real save needs, exported text is imported back byte for byte
+#[test]
+fn fixtures_round_trip_through_uesave() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/synthetic_profile.sav");
+    let saves = [read_sav(fs::read(path).unwrap()), read_sav(write_sav(&world_archive(), &Compressor::None))];
+
+    for sav_file in saves {
+        let content = sav_file.get_content().unwrap();
+        let exported = uesave::export(&sav_file.get_archive().unwrap()).unwrap();
+
+        assert!(exported.pointer("/content/object_index/0/properties").unwrap().is_object());
+
+        let text = serde_json::to_string(&exported).unwrap();
+        let imported = uesave::import(serde_json::from_str(&text).unwrap()).unwrap();
+
+        assert_content_eq("uesave", &content, &read_sav(write_sav(&imported, &Compressor::None)).get_content().unwrap());
+    }
+}
+
+// names that only differ by their number have keys of their own
+#[test]
+fn numbered_names_round_trip_through_uesave() {
+    let mut archive = sample_archive();
+    let properties = &mut archive.content.object_index[0].properties;
+
+    properties.push(serde_json::from_value(property("Slot", "IntProperty", json!({ "Int32": 1 }))).unwrap());
+    properties.push(serde_json::from_value(property("Slot", "IntProperty", json!({ "Int32": 2 }))).unwrap());
+    properties.last_mut().unwrap().name.number = Some(1);
+
+    let exported = uesave::export(&archive).unwrap();
+    let keys = exported.pointer("/content/object_index/0/properties").unwrap().as_object().unwrap();
+
+    assert!(keys.contains_key("Slot") && keys.contains_key("Slot_1"));
+
+    let imported = uesave::import(exported).unwrap();
+    let slots = imported.root().unwrap().properties.iter()
+        .filter(|property| property.name.value == "Slot")
+        .map(|property| match property.data {
+            PropertyData::Int32(value) => (property.name.number, value),
+            ref data => panic!("expected an int, got {:?}", data),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(slots, [(None, 1), (Some(1), 2)]);
+    assert!(imported.semantic_eq(&archive).unwrap());
+}
+
+// drop saves into tests/fixtures to check them too
+#[test]
+fn fixtures_round_trip() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+    let mut checked = Vec::new();
+
+    for entry in fs::read_dir(dir).expect("read tests/fixtures") {
+        let path = entry.expect("fixture entry").path();
+
+        if path.extension().is_some_and(|extension| extension == "sav") {
+            round_trip(&path.display().to_string(), fs::read(&path).expect("read fixture"));
+            checked.push(path.file_name().unwrap().to_string_lossy().into_owned());
+        }
+    }
+
+    // the synthetic profile is checked in, so there is always at least one save
+    assert!(checked.iter().any(|name| name == "synthetic_profile.sav"), "{:?}", checked);
+}
@@ -0,0 +1,222 @@
+use remnant_save_parser::properties::{
+    ArrayProperty, ByteProperty, BytePropertyValue, EnumProperty, FormatArgumentValue, HeadData, MapProperty,
+    StructProperty, TextProperty, TextPropertyData,
+};
+use remnant_save_parser::{DynamicStruct, FGuid, FName, Property, PropertyData, StructData};
+use remnant_save_parser::structs::{DateTime, Timespan};
+use serde_json::Value;
+
+fn guid() -> FGuid {
+    FGuid { a: 1, b: 2, c: 3, d: 4 }
+}
+
+fn text(source_string: &str) -> TextProperty {
+    TextProperty {
+        flags: 0,
+        data: TextPropertyData::Base {
+            namespace: "Namespace".to_owned(),
+            key: "Key".to_owned(),
+            source_string: source_string.to_owned(),
+        },
+    }
+}
+
+// one of every variant, the match in `variant` fails to compile when a new one is added
+fn all_variants() -> Vec<PropertyData> {
+    let dynamic = StructData::Dynamic(DynamicStruct {
+        properties: vec![Property {
+            name: FName::from("Count"),
+            index: 0,
+            type_name: FName::from("IntProperty"),
+            size: 4,
+            data: PropertyData::Int32(3),
+            offset: None,
+        }],
+    });
+
+    vec![
+        PropertyData::Byte(ByteProperty {
+            enum_name: FName::from("ERarity"),
+            value: BytePropertyValue::Enum(FName::from("ERarity::Rare")),
+        }),
+        PropertyData::Byte(ByteProperty {
+            enum_name: FName::none(),
+            value: BytePropertyValue::Byte(7),
+        }),
+        PropertyData::Bool(true),
+        PropertyData::Enum(EnumProperty {
+            enum_name: FName::from("ELoadoutTag"),
+            value: FName::from("ELoadoutTag::Primary"),
+        }),
+        PropertyData::Int16(-16),
+        PropertyData::Int32(-32),
+        PropertyData::Int64(-64),
+        PropertyData::UInt16(16),
+        PropertyData::UInt32(32),
+        PropertyData::UInt64(u64::MAX),
+        PropertyData::Float(1.5),
+        PropertyData::Double(-2.25),
+        PropertyData::Map(MapProperty {
+            key_type: FName::from("StructProperty"),
+            value_type: FName::from("StructProperty"),
+            elements: vec![(
+                PropertyData::StructReference(guid()),
+                PropertyData::Struct(StructProperty {
+                    struct_name: FName::none(),
+                    guid: FGuid { a: 0, b: 0, c: 0, d: 0 },
+                    data: dynamic,
+                }),
+            )],
+        }),
+        PropertyData::Array(ArrayProperty {
+            inner_type: FName::from("StructProperty"),
+            head_data: HeadData::Struct {
+                name: FName::from("Positions"),
+                type_name: FName::from("StructProperty"),
+                index: 0,
+                struct_name: FName::from("Vector"),
+                guid: guid(),
+            },
+            elements: vec![PropertyData::Struct(StructProperty {
+                struct_name: FName::from("Guid"),
+                guid: guid(),
+                data: StructData::Guid(guid()),
+            })],
+        }),
+        PropertyData::Object(-1),
+        PropertyData::SoftObject("/Game/Items/Item.Item_C".to_owned()),
+        PropertyData::Name(FName { value: "Name".to_owned(), number: Some(2) }),
+        PropertyData::Struct(StructProperty {
+            struct_name: FName::from("SoftClassPath"),
+            guid: guid(),
+            data: StructData::SoftClassPath("/Game/Characters/Player.Player_C".to_owned()),
+        }),
+        PropertyData::Str("Странник 旅人".to_owned()),
+        PropertyData::StructReference(guid()),
+        PropertyData::Text(TextProperty {
+            flags: 2,
+            data: TextPropertyData::ArgumentFormat {
+                source_format: Box::new(text("{Count} items")),
+                arguments: vec![
+                    ("Count".to_owned(), FormatArgumentValue::Int(3)),
+                    ("Label".to_owned(), FormatArgumentValue::Text(Box::new(text("Label")))),
+                ],
+            },
+        }),
+        PropertyData::Raw(vec![0, 1, 2, 255]),
+    ]
+}
+
+fn variant(data: &PropertyData) -> &'static str {
+    match data {
+        PropertyData::Byte(_) => "Byte",
+        PropertyData::Bool(_) => "Bool",
+        PropertyData::Enum(_) => "Enum",
+        PropertyData::Int16(_) => "Int16",
+        PropertyData::Int32(_) => "Int32",
+        PropertyData::Int64(_) => "Int64",
+        PropertyData::UInt16(_) => "UInt16",
+        PropertyData::UInt32(_) => "UInt32",
+        PropertyData::UInt64(_) => "UInt64",
+        PropertyData::Float(_) => "Float",
+        PropertyData::Double(_) => "Double",
+        PropertyData::Map(_) => "Map",
+        PropertyData::Array(_) => "Array",
+        PropertyData::Object(_) => "Object",
+        PropertyData::SoftObject(_) => "SoftObject",
+        PropertyData::Name(_) => "Name",
+        PropertyData::Struct(_) => "Struct",
+        PropertyData::Str(_) => "Str",
+        PropertyData::StructReference(_) => "StructReference",
+        PropertyData::Text(_) => "Text",
+        PropertyData::Raw(_) => "Raw",
+    }
+}
+
+#[test]
+fn property_data_survives_json() {
+    for data in all_variants() {
+        let json = serde_json::to_value(&data).expect("serialize");
+        let read: PropertyData = serde_json::from_value(json.clone()).expect("deserialize");
+
+        assert_eq!(variant(&read), variant(&data));
+        assert_eq!(serde_json::to_value(&read).expect("serialize again"), json, "{} changed", variant(&data));
+    }
+}
+
+#[test]
+fn byte_values_stay_distinguishable() {
+    let json = serde_json::to_value(all_variants()).expect("serialize");
+
+    let Value::Array(values) = json else {
+        panic!("expected an array");
+    };
+
+    assert!(values[0]["Byte"]["value"]["Enum"].is_object());
+    assert_eq!(values[1]["Byte"]["value"]["Byte"], 7);
+}
+
+#[test]
+fn guids_are_strings() {
+    let json = serde_json::to_value(guid()).expect("serialize");
+
+    assert_eq!(json, Value::from("00000001-0000-0002-0000-000300000004"));
+    assert_eq!(serde_json::from_value::<FGuid>(json).expect("deserialize"), guid());
+
+    // the four fields guids were serialized as before
+    let fields = serde_json::json!({ "a": 1, "b": 2, "c": 3, "d": 4 });
+
+    assert_eq!(serde_json::from_value::<FGuid>(fields).expect("deserialize fields"), guid());
+    assert!(serde_json::from_value::<FGuid>(Value::from("not a guid")).is_err());
+}
+
+#[test]
+fn date_times_are_iso_8601() {
+    let epoch = serde_json::to_value(DateTime { value: 0 }).expect("serialize");
+
+    assert_eq!(epoch, Value::from("0001-01-01T00:00:00Z"));
+
+    // 2023-07-25T12:34:56.1234567Z in 100ns ticks since 0001-01-01
+    let value = 638_258_852_961_234_567;
+    let json = serde_json::to_value(DateTime { value }).expect("serialize");
+
+    assert_eq!(json, Value::from("2023-07-25T12:34:56.123456700Z"));
+
+    for value in [0, 1, value, u64::MAX] {
+        let json = serde_json::to_value(DateTime { value }).expect("serialize");
+
+        assert_eq!(serde_json::from_value::<DateTime>(json).expect("deserialize").value, value);
+    }
+
+    let offset = serde_json::from_value::<DateTime>(Value::from("2023-07-25T14:34:56.1234567+02:00")).expect("offset");
+
+    assert_eq!(offset.value, value);
+    assert!(serde_json::from_value::<DateTime>(Value::from("0000-12-31T23:59:59Z")).is_err());
+    assert_eq!(serde_json::from_value::<DateTime>(serde_json::json!({ "value": 5 })).expect("ticks").value, 5);
+}
+
+#[test]
+fn timespans_are_hours_minutes_seconds() {
+    let cases = [
+        (0, "00:00:00"),
+        (1, "00:00:00.0000001"),
+        (90 * 60 * 10_000_000, "01:30:00"),
+        ((100 * 3600 + 2 * 60 + 3) * 10_000_000 + 5_000_000, "100:02:03.5000000"),
+    ];
+
+    for (value, text) in cases {
+        assert_eq!(serde_json::to_value(Timespan { value }).expect("serialize"), Value::from(text));
+        assert_eq!(serde_json::from_value::<Timespan>(Value::from(text)).expect("deserialize").value, value);
+    }
+
+    let max = serde_json::to_value(Timespan { value: u64::MAX }).expect("serialize");
+
+    assert_eq!(serde_json::from_value::<Timespan>(max).expect("deserialize").value, u64::MAX);
+    assert_eq!("00:00:01.5".parse::<Timespan>().unwrap().value, 15_000_000);
+
+    for invalid in ["", "1:60:00", "00:00", "00:00:00.12345678", "-1:00:00", "99999999999999:00:00"] {
+        assert!(invalid.parse::<Timespan>().is_err(), "{}", invalid);
+    }
+
+    assert_eq!(serde_json::from_value::<Timespan>(serde_json::json!({ "value": 5 })).expect("ticks").value, 5);
+}